@@ -34,6 +34,20 @@ pub enum IDAError {
     MakeSigs,
     #[error("could not get library version")]
     GetVersion,
+    #[error("{kind}: {message}{}", ida_error_suffix(.ida_error))]
+    Kind {
+        kind: IDAErrorKind,
+        message: String,
+        /// IDA's own description of the failure, when it gave one
+        ida_error: Option<String>,
+    },
+}
+
+fn ida_error_suffix(ida_error: &Option<String>) -> String {
+    ida_error
+        .as_deref()
+        .map(|reason| format!(": {reason}"))
+        .unwrap_or_default()
 }
 
 impl IDAError {
@@ -54,6 +68,95 @@ impl IDAError {
     pub fn not_found(path: impl Into<PathBuf>) -> Self {
         Self::FileNotFound(path.into())
     }
+
+    pub fn with_kind(kind: IDAErrorKind, m: impl std::fmt::Display) -> Self {
+        Self::Kind {
+            kind,
+            message: m.to_string(),
+            ida_error: None,
+        }
+    }
+
+    /// A failed IDA call, along with IDA's description of the failure if it
+    /// gave one
+    pub fn ffi_failure(m: impl std::fmt::Display, ida_error: Option<String>) -> Self {
+        Self::Kind {
+            kind: IDAErrorKind::Ffi,
+            message: m.to_string(),
+            ida_error,
+        }
+    }
+
+    pub fn validation(m: impl std::fmt::Display) -> Self {
+        Self::with_kind(IDAErrorKind::Validation, m)
+    }
+
+    pub fn not_found_with(m: impl std::fmt::Display) -> Self {
+        Self::with_kind(IDAErrorKind::NotFound, m)
+    }
+
+    pub fn already_exists(m: impl std::fmt::Display) -> Self {
+        Self::with_kind(IDAErrorKind::AlreadyExists, m)
+    }
+
+    pub fn unsupported(m: impl std::fmt::Display) -> Self {
+        Self::with_kind(IDAErrorKind::Unsupported, m)
+    }
+
+    /// Get the category of this error
+    pub fn kind(&self) -> IDAErrorKind {
+        match self {
+            Self::Kind { kind, .. } => *kind,
+            Self::FileNotFound(_) => IDAErrorKind::NotFound,
+            _ => IDAErrorKind::Ffi,
+        }
+    }
+
+    /// Get the description of this error without the kind prefix
+    pub fn message(&self) -> String {
+        match self {
+            Self::Kind {
+                message, ida_error, ..
+            } => format!("{message}{}", ida_error_suffix(ida_error)),
+            _ => self.to_string(),
+        }
+    }
+
+    /// Get IDA's own description of the failure, if it gave one
+    pub fn ida_error(&self) -> Option<&str> {
+        match self {
+            Self::Kind { ida_error, .. } => ida_error.as_deref(),
+            _ => None,
+        }
+    }
+}
+
+/// Category of an [`IDAError`], for telling errors apart without parsing
+/// their messages
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum IDAErrorKind {
+    /// A builder or argument was rejected before IDA was asked to do anything
+    Validation,
+    /// IDA refused or failed an operation
+    Ffi,
+    /// A type, function, file or other item does not exist
+    NotFound,
+    /// An item with the same name already exists
+    AlreadyExists,
+    /// The operation is not supported by IDA or by these bindings
+    Unsupported,
+}
+
+impl std::fmt::Display for IDAErrorKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Validation => "validation",
+            Self::Ffi => "ffi",
+            Self::NotFound => "not found",
+            Self::AlreadyExists => "already exists",
+            Self::Unsupported => "unsupported",
+        })
+    }
 }
 
 include_cpp! {
@@ -1047,6 +1150,7 @@ mod ffix {
 
         unsafe fn idalib_plugin_version(p: *const plugin_t) -> u64;
         unsafe fn idalib_plugin_flags(p: *const plugin_t) -> u64;
+        unsafe fn idalib_save_database(outfile: *const c_char) -> bool;
 
         unsafe fn idalib_get_library_version(
             major: *mut c_int,
@@ -1429,6 +1533,25 @@ pub mod ida {
         unsafe { ffi::close_database(save) }
     }
 
+    /// Save the database to `path`, or to its own file if `path` is `None`
+    pub fn save_database_as(path: Option<&Path>) -> Result<(), IDAError> {
+        assert!(
+            is_main_thread(),
+            "IDA cannot function correctly when not running on the main thread"
+        );
+
+        let path = path
+            .map(|path| CString::new(path.to_string_lossy().as_ref()).map_err(IDAError::ffi))
+            .transpose()?;
+        let outfile = path.as_ref().map_or(ptr::null(), |path| path.as_ptr());
+
+        if unsafe { ffix::idalib_save_database(outfile) } {
+            Ok(())
+        } else {
+            Err(IDAError::ffi_with("Failed to save the database"))
+        }
+    }
+
     pub fn library_version() -> Result<(i32, i32, i32), IDAError> {
         assert!(
             is_main_thread(),
@@ -1460,12 +1583,33 @@ pub mod types {
     };
     // CXX bridge functions for type creation
     pub use super::types_bridge::ffi_types::{
-        create_struct_type, create_union_type, add_field_to_type,
-        finalize_type, get_primitive_type_ordinal, get_type_size,
-        create_enum_type, add_enum_member,
-        create_array_type, create_pointer_type,
+        create_struct_type, create_union_type, forward_declare_struct, add_field_to_type,
+        add_baseclass, finalize_type, get_primitive_type_ordinal, get_type_size, get_type_alignment,
+        take_last_type_error,
+        set_type_alignment, set_udt_unaligned, set_field_comment, set_udt_kind, clear_udt_members,
+        reset_to_empty_udt, save_type_definition, restore_type_definition,
+        set_udt_cpp_object, set_udt_vftable, set_udt_member_vftable, set_udt_member_type,
+        rename_udt_member,
+        create_enum_type, add_enum_member, set_enum_bitmask, set_enum_signed,
+        get_enum_member_by_value, set_enum_radix,
+        create_array_type, create_pointer_type, create_qualified_type, create_sized_pointer_type,
         add_bitfield_to_struct,
-        create_function_type, add_function_parameter,
-        set_function_attributes, create_function_pointer_type,
+        create_function_type, add_function_parameter, add_function_parameter_in_register,
+        set_function_attributes, set_function_purged, set_function_spoiled,
+        set_function_return_register, create_function_pointer_type,
+        get_pointer_size, get_wchar_size, get_or_create_primitive_typedef, create_typedef,
+        get_array_element_ordinal, get_array_length, is_pointer_type, get_pointer_target,
+        get_type_ordinal_by_name, find_type_referrer, delete_type, type_name_exists, rename_type,
+        apply_type_to_address, is_function_type, apply_function_type, print_type_decl,
+        parse_type_decl, parse_header_types, demangle_symbol, set_database_compiler,
+        get_type_name, is_library_type, get_primitive_type_code, get_typedef_target,
+        get_udt_member_count, is_union_type, get_udt_member_name, get_udt_member_type,
+        get_udt_member_offset, get_udt_member_size, get_udt_member_bitfield,
+        get_udt_member_bitfield_storage, get_udt_member_names, get_udt_member_types,
+        get_udt_member_offsets, get_udt_member_sizes, get_udt_member_bitfields,
+        get_udt_member_comments, get_udt_member_roles, get_udt_layout,
+        describe_udt_member_type, describe_typedef_target,
+        get_enum_member_count, get_enum_member_name, get_enum_member_value, is_bitmask_enum,
+        print_type_expr, parse_type_expr, compare_types, get_type_kind,
     };
 }
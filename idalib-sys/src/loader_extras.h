@@ -13,3 +13,8 @@ uint64_t idalib_plugin_version(const plugin_t *p) {
 uint64_t idalib_plugin_flags(const plugin_t *p) {
   return p == nullptr ? 0 : p->flags;
 }
+
+// Save the database to `outfile`, or to its own file if `outfile` is null
+bool idalib_save_database(const char *outfile) {
+  return save_database(outfile, uint32(-1));
+}
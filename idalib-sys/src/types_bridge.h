@@ -2,11 +2,56 @@
 
 #include <cstdint>
 #include <string>
+#include <utility>
+#include <vector>
 #include "rust/cxx.h"
 #include "typeinf.hpp"
 #include "ida.hpp"
 #include "idp.hpp"
 #include "loader.hpp"
+#include "name.hpp"
+
+// Reason for the most recent failure in this bridge, handed to Rust by
+// take_last_type_error
+inline std::string& last_type_error() {
+    static std::string error;
+    return error;
+}
+
+// Record why a type library call failed; returns whether it succeeded
+inline bool check_terr(tinfo_code_t code) {
+    if (code != TERR_OK) {
+        last_type_error() = tinfo_errstr(code);
+    }
+    return code == TERR_OK;
+}
+
+// Take the reason for the most recent failure, falling back to IDA's last
+// error code; empty if neither is set
+inline rust::String take_last_type_error() {
+    std::string error;
+    error.swap(last_type_error());
+    if (error.empty() && get_qerrno() != eOk) {
+        error = qstrerror(-1);
+        set_qerrno(eOk);
+    }
+    return rust::String(error);
+}
+
+// Ordinal of the forward declaration (`struct name;`) called `name`, or 0
+// if there is none
+inline uint32_t find_forward_decl(til_t* til, const std::string& name) {
+    if (name.empty()) return 0;
+    
+    uint32_t ordinal = get_type_ordinal(til, name.c_str());
+    if (ordinal == 0) return 0;
+    
+    tinfo_t tif;
+    if (!tif.get_numbered_type(til, ordinal) || !tif.is_forward_decl()) {
+        return 0;
+    }
+    return ordinal;
+}
 
 // Create a new struct type and return its ordinal
 inline uint32_t create_struct_type(rust::Str name) {
@@ -14,8 +59,11 @@ inline uint32_t create_struct_type(rust::Str name) {
     til_t* til = get_idati();
     if (!til) return 0;
     
-    // Allocate a new ordinal for this type
-    uint32_t ordinal = alloc_type_ordinal(til);
+    // Complete a forward declaration of the same name in place, so pointers
+    // to it see the definition; otherwise allocate a new ordinal
+    uint32_t ordinal = find_forward_decl(til, name_str);
+    bool completing = ordinal != 0;
+    if (!completing) ordinal = alloc_type_ordinal(til);
     if (ordinal == 0) return 0;
     
     // Create empty struct
@@ -28,12 +76,14 @@ inline uint32_t create_struct_type(rust::Str name) {
     }
     
     // Save with the allocated ordinal
-    if (tif.set_numbered_type(til, ordinal, NTF_TYPE) != 0) {
+    if (!check_terr(tif.set_numbered_type(til, ordinal, completing ? NTF_REPLACE : NTF_TYPE))) {
         return 0;
     }
     
-    // Also save with name
-    tif.set_named_type(til, name_str.c_str(), NTF_TYPE);
+    // Also save with name; inline (anonymous) types have none
+    if (!name_str.empty() && !completing) {
+        tif.set_named_type(til, name_str.c_str(), NTF_TYPE);
+    }
     
     return ordinal;
 }
@@ -44,7 +94,9 @@ inline uint32_t create_union_type(rust::Str name) {
     til_t* til = get_idati();
     if (!til) return 0;
     
-    uint32_t ordinal = alloc_type_ordinal(til);
+    uint32_t ordinal = find_forward_decl(til, name_str);
+    bool completing = ordinal != 0;
+    if (!completing) ordinal = alloc_type_ordinal(til);
     if (ordinal == 0) return 0;
     
     tinfo_t tif;
@@ -55,15 +107,31 @@ inline uint32_t create_union_type(rust::Str name) {
         return 0;
     }
     
-    if (tif.set_numbered_type(til, ordinal, NTF_TYPE) != 0) {
+    if (!check_terr(tif.set_numbered_type(til, ordinal, completing ? NTF_REPLACE : NTF_TYPE))) {
         return 0;
     }
     
-    tif.set_named_type(til, name_str.c_str(), NTF_TYPE);
+    if (!name_str.empty() && !completing) {
+        tif.set_named_type(til, name_str.c_str(), NTF_TYPE);
+    }
     
     return ordinal;
 }
 
+// Declare `struct name;` without a definition and return its ordinal
+inline uint32_t forward_declare_struct(rust::Str name) {
+    std::string name_str(name);
+    til_t* til = get_idati();
+    if (!til || name_str.empty()) return 0;
+    
+    tinfo_t tif;
+    if (!check_terr(tif.create_forward_decl(til, BTF_STRUCT, name_str.c_str()))) {
+        return 0;
+    }
+    
+    return get_type_ordinal(til, name_str.c_str());
+}
+
 // Add a field to an existing struct/union
 inline bool add_field_to_type(
     uint32_t type_ordinal,
@@ -99,7 +167,12 @@ inline bool add_field_to_type(
     member.offset = offset * 8; // Convert to bits
     member.size = field_tif.get_size() * 8;
     
-    udt.push_back(member);
+    // Keep members sorted by offset, so fields can be inserted between them
+    size_t index = udt.size();
+    while (index > 0 && udt[index - 1].offset > member.offset) {
+        index--;
+    }
+    udt.insert(udt.begin() + index, member);
     
     // Recreate the type with the new field
     tinfo_t new_tif;
@@ -108,473 +181,2234 @@ inline bool add_field_to_type(
     }
     
     // Update the type
-    return new_tif.set_numbered_type(til, type_ordinal, NTF_REPLACE) == 0;
+    return check_terr(new_tif.set_numbered_type(til, type_ordinal, NTF_REPLACE));
 }
 
-// Finalize type (ensure it's properly saved)
-inline bool finalize_type(uint32_t type_ordinal) {
+// Add a base class member at the start of a struct
+inline bool add_baseclass(
+    uint32_t type_ordinal,
+    rust::Str field_name,
+    uint32_t base_ordinal
+) {
     til_t* til = get_idati();
     if (!til) return false;
     
-    tinfo_t tif;
-    if (!tif.get_numbered_type(til, type_ordinal)) {
+    tinfo_t struct_tif;
+    if (!struct_tif.get_numbered_type(til, type_ordinal)) {
         return false;
     }
     
-    // Force synchronization
-    return tif.set_numbered_type(til, type_ordinal, NTF_REPLACE) == 0;
-}
-
-// Get or create primitive type ordinal
-inline uint32_t get_primitive_type_ordinal(uint32_t bt_type) {
-    tinfo_t tif;
-    
-    // Create the primitive type
-    type_t ida_type = static_cast<type_t>(bt_type);
-    if (!tif.create_simple_type(ida_type)) {
-        // Fallback to predefined ordinals for common types
-        switch (bt_type) {
-            case BTF_VOID: return 1;
-            case BTF_INT8: return 2;
-            case BTF_INT16: return 3;
-            case BTF_INT32: return 4;
-            case BTF_INT64: return 5;
-            case BTF_UINT8: return 6;
-            case BTF_UINT16: return 7;
-            case BTF_UINT32: return 8;
-            case BTF_UINT64: return 9;
-            case BTF_FLOAT: return 10;
-            case BTF_DOUBLE: return 11;
-            default: return 0;
-        }
+    tinfo_t base_tif;
+    if (!base_tif.get_numbered_type(til, base_ordinal)) {
+        return false;
+    }
+    if (!base_tif.is_struct()) {
+        last_type_error() = "base class must be a struct";
+        return false;
     }
     
-    til_t* til = get_idati();
-    if (!til) return 0;
-    
-    // Try to find existing ordinal
-    uint32_t limit = get_ordinal_limit(til);
-    for (uint32_t i = 1; i < limit; i++) {
-        tinfo_t check_tif;
-        if (check_tif.get_numbered_type(til, i)) {
-            if (tif.equals_to(check_tif)) {
-                return i;
-            }
-        }
+    udt_type_data_t udt;
+    if (!struct_tif.get_udt_details(&udt)) {
+        return false;
     }
     
-    // Create new ordinal
-    uint32_t new_ordinal = alloc_type_ordinal(til);
-    if (new_ordinal == 0) return 0;
+    udm_t member;
+    member.name = qstring(field_name.data(), field_name.size());
+    member.type = base_tif;
+    member.offset = 0;
+    member.size = base_tif.get_size() * 8;
+    member.set_baseclass();
     
-    if (tif.set_numbered_type(til, new_ordinal, NTF_TYPE) != 0) {
-        return 0;
+    udt.insert(udt.begin(), member);
+    
+    tinfo_t new_tif;
+    if (!new_tif.create_udt(udt)) {
+        return false;
     }
     
-    return new_ordinal;
+    return check_terr(new_tif.set_numbered_type(til, type_ordinal, NTF_REPLACE));
 }
 
-// Get size of a type
-inline uint64_t get_type_size(uint32_t ordinal) {
+// Set the comment of a struct/union member
+inline bool set_field_comment(
+    uint32_t type_ordinal,
+    rust::Str field_name,
+    rust::Str comment,
+    bool repeatable
+) {
     til_t* til = get_idati();
-    if (!til) return 0;
+    if (!til) return false;
     
-    tinfo_t tif;
-    if (!tif.get_numbered_type(til, ordinal)) {
-        return 0;
+    tinfo_t struct_tif;
+    if (!struct_tif.get_numbered_type(til, type_ordinal)) {
+        return false;
+    }
+    
+    // Find the member by name
+    udm_t udm;
+    udm.name = qstring(field_name.data(), field_name.size());
+    int index = struct_tif.find_udm(&udm, STRMEM_NAME);
+    if (index < 0) {
+        return false;
     }
     
-    return tif.get_size();
+    std::string comment_str(comment);
+    return check_terr(struct_tif.set_udm_cmt(index, comment_str.c_str(), !repeatable));
 }
 
-// ============================================================================
-// Enum Type Functions
-// ============================================================================
-
-// Create a new enum type and return its ordinal
-inline uint32_t create_enum_type(rust::Str name, uint32_t width) {
-    std::string name_str(name);
+// Set the packing and declared alignment of a struct/union (0 = unchanged)
+inline bool set_type_alignment(uint32_t type_ordinal, uint32_t pack, uint32_t align) {
     til_t* til = get_idati();
-    if (!til) return 0;
-    
-    // Allocate ordinal for enum
-    uint32_t ordinal = alloc_type_ordinal(til);
-    if (ordinal == 0) return 0;
+    if (!til) return false;
     
-    // Create enum type
-    tinfo_t tif;
-    enum_type_data_t etd;
-    // Set width through set_nbytes method
-    if (!etd.set_nbytes(width)) {
-        return 0; // Invalid width
+    tinfo_t struct_tif;
+    if (!struct_tif.get_numbered_type(til, type_ordinal)) {
+        return false;
     }
     
-    if (!tif.create_enum(etd)) {
-        return 0;
+    udt_type_data_t udt;
+    if (!struct_tif.get_udt_details(&udt)) {
+        return false;
     }
     
-    // Save the enum type
-    if (tif.set_numbered_type(til, ordinal, NTF_TYPE) != 0) {
-        return 0;
+    // Both are stored as shift amounts; sda is offset by one (0 = unspecified)
+    if (pack != 0) {
+        udt.pack = static_cast<uchar>(log2ceil(pack));
+    }
+    if (align != 0) {
+        udt.sda = static_cast<uchar>(log2ceil(align) + 1);
     }
     
-    // Also save with name
-    tif.set_named_type(til, name_str.c_str(), NTF_TYPE);
+    tinfo_t new_tif;
+    if (!new_tif.create_udt(udt)) {
+        return false;
+    }
     
-    return ordinal;
+    return check_terr(new_tif.set_numbered_type(til, type_ordinal, NTF_REPLACE));
 }
 
-// Add a member to an enum
-inline bool add_enum_member(uint32_t enum_ordinal, rust::Str member_name, int64_t value) {
+// Set or clear TAUDT_* attribute bits of a struct/union
+inline bool update_udt_bits(uint32_t type_ordinal, uint32_t bits, bool on) {
     til_t* til = get_idati();
     if (!til) return false;
     
-    // Get the enum type
-    tinfo_t enum_tif;
-    if (!enum_tif.get_numbered_type(til, enum_ordinal)) {
+    tinfo_t struct_tif;
+    if (!struct_tif.get_numbered_type(til, type_ordinal)) {
         return false;
     }
     
-    // Get enum details
-    enum_type_data_t etd;
-    if (!enum_tif.get_enum_details(&etd)) {
+    udt_type_data_t udt;
+    if (!struct_tif.get_udt_details(&udt)) {
         return false;
     }
     
-    // Add new member
-    edm_t member;
-    member.name = qstring(member_name.data(), member_name.size());
-    member.value = value;
-    
-    etd.push_back(member);
+    if (on) {
+        udt.taudt_bits |= bits;
+    } else {
+        udt.taudt_bits &= ~bits;
+    }
     
-    // Recreate enum with new member
     tinfo_t new_tif;
-    if (!new_tif.create_enum(etd)) {
+    if (!new_tif.create_udt(udt)) {
         return false;
     }
     
-    // Update the type
-    return new_tif.set_numbered_type(til, enum_ordinal, NTF_REPLACE) == 0;
+    return check_terr(new_tif.set_numbered_type(til, type_ordinal, NTF_REPLACE));
 }
 
-// ============================================================================
-// Array Type Functions
-// ============================================================================
+// Mark a struct as unaligned, so its size is not padded up to its alignment
+inline bool set_udt_unaligned(uint32_t type_ordinal, bool unaligned) {
+    return update_udt_bits(type_ordinal, TAUDT_UNALIGNED, unaligned);
+}
 
-// Create an array type
-inline uint32_t create_array_type(uint32_t element_type_ordinal, uint32_t num_elements) {
+// Mark a struct as a C++ object (class) rather than plain data
+inline bool set_udt_cpp_object(uint32_t type_ordinal, bool cpp_object) {
+    return update_udt_bits(type_ordinal, TAUDT_CPPOBJ, cpp_object);
+}
+
+// Mark a struct as the virtual function table of a class
+inline bool set_udt_vftable(uint32_t type_ordinal, bool vftable) {
+    return update_udt_bits(type_ordinal, TAUDT_VFTABLE, vftable);
+}
+
+// Mark a member as the pointer to its class's virtual function table
+inline bool set_udt_member_vftable(uint32_t type_ordinal, rust::Str field_name) {
     til_t* til = get_idati();
-    if (!til) return 0;
+    if (!til) return false;
     
-    // Get element type
-    tinfo_t elem_tif;
-    if (!elem_tif.get_numbered_type(til, element_type_ordinal)) {
-        return 0;
+    tinfo_t struct_tif;
+    if (!struct_tif.get_numbered_type(til, type_ordinal)) {
+        return false;
     }
     
-    // Allocate ordinal
-    uint32_t ordinal = alloc_type_ordinal(til);
-    if (ordinal == 0) return 0;
-    
-    // Create array type
-    tinfo_t tif;
-    array_type_data_t atd;
-    atd.elem_type = elem_tif;
-    atd.nelems = num_elements;
+    udm_t udm;
+    udm.name = qstring(field_name.data(), field_name.size());
+    int index = struct_tif.find_udm(&udm, STRMEM_NAME);
+    if (index < 0) {
+        return false;
+    }
     
-    if (!tif.create_array(atd)) {
-        return 0;
+    udt_type_data_t udt;
+    if (!struct_tif.get_udt_details(&udt)) {
+        return false;
     }
+    udt[index].set_vftable();
     
-    // Save the array type
-    if (tif.set_numbered_type(til, ordinal, NTF_TYPE) != 0) {
-        return 0;
+    tinfo_t new_tif;
+    if (!new_tif.create_udt(udt)) {
+        return false;
     }
     
-    return ordinal;
+    return check_terr(new_tif.set_numbered_type(til, type_ordinal, NTF_REPLACE));
 }
 
-// ============================================================================
-// Bitfield Type Functions
-// ============================================================================
-
-// Add a bitfield member to a struct
-inline bool add_bitfield_to_struct(
-    uint32_t struct_ordinal,
-    rust::Str field_name,
-    uint32_t bit_offset,
-    uint32_t bit_width,
-    bool is_unsigned
-) {
+// Change the type of a struct/union member; with `may_grow` the member may
+// grow over (and remove) the members that follow it
+inline bool set_udt_member_type(uint32_t type_ordinal, rust::Str field_name,
+                                uint32_t member_type_ordinal, bool may_grow) {
     til_t* til = get_idati();
     if (!til) return false;
     
-    // Get the struct type
     tinfo_t struct_tif;
-    if (!struct_tif.get_numbered_type(til, struct_ordinal)) {
+    if (!struct_tif.get_numbered_type(til, type_ordinal)) {
         return false;
     }
     
-    // Get existing UDT details
-    udt_type_data_t udt;
-    if (!struct_tif.get_udt_details(&udt)) {
+    tinfo_t member_tif;
+    if (!member_tif.get_numbered_type(til, member_type_ordinal)) {
         return false;
     }
     
-    // Create bitfield type for the member
-    // Calculate the nbytes based on the offset and width
-    uint32_t end_bit = bit_offset + bit_width;
-    uint32_t nbytes = 1;
-    if (end_bit > 8) nbytes = 2;
-    if (end_bit > 16) nbytes = 4;
-    if (end_bit > 32) nbytes = 8;
-    
-    tinfo_t bitfield_tif;
-    bitfield_type_data_t bfd(nbytes, bit_width, is_unsigned);
-    if (!bitfield_tif.create_bitfield(bfd)) {
+    udm_t udm;
+    udm.name = qstring(field_name.data(), field_name.size());
+    int index = struct_tif.find_udm(&udm, STRMEM_NAME);
+    if (index < 0) {
         return false;
     }
     
-    // Add bitfield member
-    udm_t member;
-    member.name = qstring(field_name.data(), field_name.size());
-    member.type = bitfield_tif;
-    member.offset = bit_offset; // Offset in bits
-    member.size = bit_width;    // Size in bits
+    return check_terr(struct_tif.set_udm_type(index, member_tif, may_grow ? ETF_MAY_DESTROY : 0));
+}
+
+// Rename a struct/union member
+inline bool rename_udt_member(uint32_t type_ordinal, rust::Str old_name, rust::Str new_name) {
+    til_t* til = get_idati();
+    if (!til) return false;
     
-    udt.push_back(member);
+    tinfo_t struct_tif;
+    if (!struct_tif.get_numbered_type(til, type_ordinal)) {
+        return false;
+    }
     
-    // Recreate the type with the new bitfield
-    tinfo_t new_tif;
-    if (!new_tif.create_udt(udt)) {
+    udm_t udm;
+    udm.name = qstring(old_name.data(), old_name.size());
+    int index = struct_tif.find_udm(&udm, STRMEM_NAME);
+    if (index < 0) {
         return false;
     }
     
-    // Update the type
-    return new_tif.set_numbered_type(til, struct_ordinal, NTF_REPLACE) == 0;
+    qstring name(new_name.data(), new_name.size());
+    return check_terr(struct_tif.rename_udm(index, name.c_str()));
 }
 
-// ============================================================================
-// Function Type Functions
-// ============================================================================
-
-// Create a function type
-inline uint32_t create_function_type(
-    uint32_t return_type_ordinal,
-    uint32_t calling_convention,
-    bool is_vararg
-) {
+// Turn an empty struct into an empty union or vice versa; fails for
+// types that already have members
+inline bool set_udt_kind(uint32_t type_ordinal, bool is_union) {
     til_t* til = get_idati();
-    if (!til) return 0;
-    
-    // Allocate ordinal
-    uint32_t ordinal = alloc_type_ordinal(til);
-    if (ordinal == 0) return 0;
-    
-    // Create function type
-    func_type_data_t ftd;
+    if (!til) return false;
     
-    // Set return type
-    if (return_type_ordinal != 0) {
-        tinfo_t ret_tif;
-        if (!ret_tif.get_numbered_type(til, return_type_ordinal)) {
-            return 0;
-        }
-        ftd.rettype = ret_tif;
+    tinfo_t tif;
+    if (!tif.get_numbered_type(til, type_ordinal)) {
+        return false;
     }
     
-    // Set calling convention
-#if IDA_SDK_VERSION >= 920
-    ftd.set_cc(calling_convention);
+    udt_type_data_t udt;
+    if (!tif.get_udt_details(&udt) || !udt.empty()) {
+        return false;
+    }
     
-    // Set vararg flag
-    if (is_vararg) {
-        ftd.set_cc(ftd.get_cc() | CM_CC_ELLIPSIS);
+    if (udt.is_union == is_union) {
+        return true;
     }
-#else
-    ftd.cc = calling_convention;
     
-    // Set vararg flag
-    if (is_vararg) {
-        ftd.cc = ftd.cc | CM_CC_ELLIPSIS;
+    udt.is_union = is_union;
+    tinfo_t new_tif;
+    if (!new_tif.create_udt(udt)) {
+        return false;
     }
-#endif
     
-    // Create the function type
+    return check_terr(new_tif.set_numbered_type(til, type_ordinal, NTF_REPLACE));
+}
+
+// Remove all members of a struct/union, keeping its name and kind
+inline bool clear_udt_members(uint32_t type_ordinal) {
+    til_t* til = get_idati();
+    if (!til) return false;
+    
     tinfo_t tif;
-    if (!tif.create_func(ftd)) {
-        return 0;
+    if (!tif.get_numbered_type(til, type_ordinal) || !tif.is_udt()) {
+        return false;
     }
     
-    // Save the function type
-    if (tif.set_numbered_type(til, ordinal, NTF_TYPE) != 0) {
-        return 0;
+    udt_type_data_t udt;
+    udt.is_union = tif.is_union();
+    tinfo_t new_tif;
+    if (!new_tif.create_udt(udt)) {
+        return false;
     }
     
-    return ordinal;
+    return check_terr(new_tif.set_numbered_type(til, type_ordinal, NTF_REPLACE));
 }
 
-// Add a parameter to a function type
-inline bool add_function_parameter(
-    uint32_t func_ordinal,
-    rust::Str param_name,
-    uint32_t param_type_ordinal,
-    bool is_hidden
-) {
+// Replace the type with this ordinal, whatever its kind, by an empty
+// struct/union of the same name, so references to the ordinal are kept
+inline bool reset_to_empty_udt(uint32_t type_ordinal, bool is_union) {
     til_t* til = get_idati();
     if (!til) return false;
     
-    // Get the function type
-    tinfo_t func_tif;
-    if (!func_tif.get_numbered_type(til, func_ordinal)) {
-        return false;
-    }
-    
-    // Get function details
-    func_type_data_t ftd;
-    if (!func_tif.get_func_details(&ftd)) {
+    const char* existing_name = get_numbered_type_name(til, type_ordinal);
+    if (existing_name == nullptr) {
+        last_type_error() = "type #" + std::to_string(type_ordinal) + " does not exist";
         return false;
     }
+    qstring name(existing_name);
     
-    // Get parameter type
-    tinfo_t param_tif;
-    if (!param_tif.get_numbered_type(til, param_type_ordinal)) {
+    udt_type_data_t udt;
+    udt.is_union = is_union;
+    tinfo_t tif;
+    if (!tif.create_udt(udt)) {
         return false;
     }
     
-    // Create new parameter
-    funcarg_t arg;
-    arg.name = qstring(param_name.data(), param_name.size());
-    arg.type = param_tif;
-    if (is_hidden) {
-        arg.flags |= FAI_HIDDEN;
+    return check_terr(tif.set_numbered_type(til, type_ordinal, NTF_REPLACE, name.c_str()));
+}
+
+// Save the definition of a numbered type, with its member names and
+// comments, so that restore_type_definition can put it back after a failed
+// replacement. Each part is stored after a 4-byte length; empty if the type
+// does not exist
+inline rust::Vec<uint8_t> save_type_definition(uint32_t type_ordinal) {
+    rust::Vec<uint8_t> saved;
+    til_t* til = get_idati();
+    if (!til) return saved;
+    
+    const type_t* type = nullptr;
+    const p_list* fields = nullptr;
+    const char* cmt = nullptr;
+    const p_list* fieldcmts = nullptr;
+    sclass_t sclass = sc_unk;
+    if (!get_numbered_type(til, type_ordinal, &type, &fields, &cmt, &fieldcmts, &sclass)) {
+        return saved;
     }
     
-    // Add parameter to function
-    ftd.push_back(arg);
+    auto append = [&saved](const void* data) {
+        const char* bytes = static_cast<const char*>(data);
+        uint32_t size = bytes == nullptr ? 0 : uint32_t(qstrlen(bytes));
+        for (int shift = 0; shift < 32; shift += 8) {
+            saved.push_back(uint8_t(size >> shift));
+        }
+        for (uint32_t i = 0; i < size; i++) {
+            saved.push_back(uint8_t(bytes[i]));
+        }
+    };
+    append(type);
+    append(fields);
+    append(cmt);
+    append(fieldcmts);
+    saved.push_back(uint8_t(sclass));
+    return saved;
+}
+
+// Put back a definition saved by save_type_definition, keeping the name the
+// type has now
+inline bool restore_type_definition(uint32_t type_ordinal, rust::Slice<const uint8_t> saved) {
+    til_t* til = get_idati();
+    if (!til) return false;
     
-    // Recreate function type with new parameter
-    tinfo_t new_tif;
-    if (!new_tif.create_func(ftd)) {
+    const char* name = get_numbered_type_name(til, type_ordinal);
+    if (name == nullptr) {
+        last_type_error() = "type #" + std::to_string(type_ordinal) + " does not exist";
         return false;
     }
-    
-    // Update the type
-    return new_tif.set_numbered_type(til, func_ordinal, NTF_REPLACE) == 0;
+    qstring type_name(name);
+    
+    size_t pos = 0;
+    qstring parts[4];
+    for (auto& part : parts) {
+        if (saved.size() < pos + 4) {
+            last_type_error() = "truncated type definition";
+            return false;
+        }
+        uint32_t size = 0;
+        for (int shift = 0; shift < 32; shift += 8) {
+            size |= uint32_t(saved[pos++]) << shift;
+        }
+        if (saved.size() < pos + size) {
+            last_type_error() = "truncated type definition";
+            return false;
+        }
+        part.append(reinterpret_cast<const char*>(saved.data() + pos), size);
+        pos += size;
+    }
+    if (saved.size() != pos + 1) {
+        last_type_error() = "truncated type definition";
+        return false;
+    }
+    sclass_t sclass = sclass_t(saved[pos]);
+    
+    auto part_or_null = [](const qstring& part) {
+        return part.empty() ? nullptr : part.c_str();
+    };
+    return check_terr(set_numbered_type(
+        til,
+        type_ordinal,
+        NTF_REPLACE,
+        type_name.c_str(),
+        reinterpret_cast<const type_t*>(parts[0].c_str()),
+        reinterpret_cast<const p_list*>(part_or_null(parts[1])),
+        part_or_null(parts[2]),
+        reinterpret_cast<const p_list*>(part_or_null(parts[3])),
+        &sclass));
 }
 
-// Set function attributes
-inline bool set_function_attributes(
-    uint32_t func_ordinal,
-    bool is_noreturn,
-    bool is_pure,
-    bool is_static,
-    bool is_virtual,
-    bool is_const,
-    bool is_constructor,
-    bool is_destructor
-) {
+// Finalize type (ensure it's properly saved)
+inline bool finalize_type(uint32_t type_ordinal) {
     til_t* til = get_idati();
     if (!til) return false;
     
-    // Get the function type
-    tinfo_t func_tif;
-    if (!func_tif.get_numbered_type(til, func_ordinal)) {
+    tinfo_t tif;
+    if (!tif.get_numbered_type(til, type_ordinal)) {
+        last_type_error() = "type #" + std::to_string(type_ordinal) + " does not exist";
         return false;
     }
     
-    // Get function details
-    func_type_data_t ftd;
-    if (!func_tif.get_func_details(&ftd)) {
-        return false;
+    // Force synchronization
+    return check_terr(tif.set_numbered_type(til, type_ordinal, NTF_REPLACE));
+}
+
+// Get or create primitive type ordinal
+inline uint32_t get_primitive_type_ordinal(uint32_t bt_type) {
+    tinfo_t tif;
+    
+    // Create the primitive type
+    type_t ida_type = static_cast<type_t>(bt_type);
+    if (!tif.create_simple_type(ida_type)) {
+        // Fallback to predefined ordinals for common types
+        switch (bt_type) {
+            case BTF_VOID: return 1;
+            case BTF_INT8: return 2;
+            case BTF_INT16: return 3;
+            case BTF_INT32: return 4;
+            case BTF_INT64: return 5;
+            case BTF_UINT8: return 6;
+            case BTF_UINT16: return 7;
+            case BTF_UINT32: return 8;
+            case BTF_UINT64: return 9;
+            case BTF_FLOAT: return 10;
+            case BTF_DOUBLE: return 11;
+            default: return 0;
+        }
     }
     
-    // Set attributes
-    if (is_noreturn) ftd.flags |= FTI_NORET;
-    if (is_pure) ftd.flags |= FTI_PURE;
-    if (is_static) ftd.flags |= FTI_STATIC;
-    if (is_virtual) ftd.flags |= FTI_VIRTUAL;
-    if (is_const) ftd.flags |= FTI_CONST;
-    if (is_constructor) ftd.flags |= FTI_CTOR;
-    if (is_destructor) ftd.flags |= FTI_DTOR;
+    til_t* til = get_idati();
+    if (!til) return 0;
     
-    // Recreate function type with new attributes
-    tinfo_t new_tif;
-    if (!new_tif.create_func(ftd)) {
-        return false;
+    // Try to find existing ordinal
+    uint32_t limit = get_ordinal_limit(til);
+    for (uint32_t i = 1; i < limit; i++) {
+        tinfo_t check_tif;
+        if (check_tif.get_numbered_type(til, i)) {
+            if (tif.equals_to(check_tif)) {
+                return i;
+            }
+        }
     }
     
-    // Update the type
-    return new_tif.set_numbered_type(til, func_ordinal, NTF_REPLACE) == 0;
+    // Create new ordinal
+    uint32_t new_ordinal = alloc_type_ordinal(til);
+    if (new_ordinal == 0) return 0;
+    
+    if (!check_terr(tif.set_numbered_type(til, new_ordinal, NTF_TYPE))) {
+        return 0;
+    }
+    
+    return new_ordinal;
 }
 
-// Create a function pointer type
-inline uint32_t create_function_pointer_type(uint32_t func_type_ordinal) {
+// Get the default pointer size of the database in bytes
+inline uint32_t get_pointer_size() {
+    if (inf_is_64bit()) return 8;
+    if (inf_is_16bit()) return 2;
+    return 4;
+}
+
+// Size of wchar_t for the database's compiler: 2 bytes for compilers and
+// binaries targeting Windows, 4 bytes otherwise
+inline uint32_t get_wchar_size() {
+    switch (inf_get_cc_id()) {
+        case COMP_MS:
+        case COMP_BC:
+        case COMP_WATCOM:
+        case COMP_VISAGE:
+            return 2;
+        default:
+            return inf_get_filetype() == f_PE ? 2 : 4;
+    }
+}
+
+// Get size of a type
+inline uint64_t get_type_size(uint32_t ordinal) {
     til_t* til = get_idati();
     if (!til) return 0;
     
-    // Get the function type
-    tinfo_t func_tif;
-    if (!func_tif.get_numbered_type(til, func_type_ordinal)) {
+    tinfo_t tif;
+    if (!tif.get_numbered_type(til, ordinal)) {
         return 0;
     }
     
-    // Allocate ordinal for the pointer
-    uint32_t ordinal = alloc_type_ordinal(til);
-    if (ordinal == 0) return 0;
+    // Opaque/incomplete types have no size
+    size_t size = tif.get_size();
+    return size == BADSIZE ? 0 : size;
+}
+
+// Compute the natural alignment of a type in bytes (0 if unknown)
+inline uint64_t calc_type_alignment(const tinfo_t& tif) {
+    if (tif.is_array()) {
+        return calc_type_alignment(tif.get_array_element());
+    }
     
-    // Create pointer to function
-    tinfo_t ptr_tif;
-    ptr_type_data_t ptd;
-    ptd.taptr_bits = 0;
-    ptd.obj_type = func_tif;
+    if (tif.is_udt()) {
+        udt_type_data_t udt;
+        if (!tif.get_udt_details(&udt)) {
+            return 0;
+        }
+        return udt.effalign > 0 ? udt.effalign : 1;
+    }
     
-    if (!ptr_tif.create_ptr(ptd)) {
+    // Scalars are aligned to their size, rounded down to a power of two
+    size_t size = tif.get_size();
+    if (size == BADSIZE || size == 0) {
         return 0;
     }
+    return uint64_t(1) << log2floor(size);
+}
+
+// Get the natural alignment of a type
+inline uint64_t get_type_alignment(uint32_t ordinal) {
+    til_t* til = get_idati();
+    if (!til) return 0;
     
-    // Save the function pointer type
-    if (ptr_tif.set_numbered_type(til, ordinal, NTF_TYPE) != 0) {
+    tinfo_t tif;
+    if (!tif.get_numbered_type(til, ordinal)) {
         return 0;
     }
     
-    return ordinal;
+    return calc_type_alignment(tif);
 }
 
 // ============================================================================
-// Pointer Type Functions
+// Enum Type Functions
 // ============================================================================
 
-// Create a pointer type
-inline uint32_t create_pointer_type(uint32_t target_type_ordinal) {
+// Create a new enum type and return its ordinal
+inline uint32_t create_enum_type(rust::Str name, uint32_t width) {
+    std::string name_str(name);
     til_t* til = get_idati();
     if (!til) return 0;
     
-    // Get target type
-    tinfo_t target_tif;
-    if (!target_tif.get_numbered_type(til, target_type_ordinal)) {
-        return 0;
-    }
-    
-    // Allocate ordinal
+    // Allocate ordinal for enum
     uint32_t ordinal = alloc_type_ordinal(til);
     if (ordinal == 0) return 0;
     
-    // Create pointer type
+    // Create enum type
     tinfo_t tif;
-    ptr_type_data_t ptd;
-    ptd.taptr_bits = 0; // Standard pointer (no special attributes)
-    ptd.obj_type = target_tif;
+    enum_type_data_t etd;
+    // Set width through set_nbytes method
+    if (!etd.set_nbytes(width)) {
+        return 0; // Invalid width
+    }
     
-    if (!tif.create_ptr(ptd)) {
+    if (!tif.create_enum(etd)) {
         return 0;
     }
     
-    // Save the pointer type
-    if (tif.set_numbered_type(til, ordinal, NTF_TYPE) != 0) {
+    // Save the enum type
+    if (!check_terr(tif.set_numbered_type(til, ordinal, NTF_TYPE))) {
+        return 0;
+    }
+    
+    // Also save with name
+    tif.set_named_type(til, name_str.c_str(), NTF_TYPE);
+    
+    return ordinal;
+}
+
+// Add a member to an enum
+inline bool add_enum_member(uint32_t enum_ordinal, rust::Str member_name, int64_t value) {
+    til_t* til = get_idati();
+    if (!til) return false;
+    
+    // Get the enum type
+    tinfo_t enum_tif;
+    if (!enum_tif.get_numbered_type(til, enum_ordinal)) {
+        return false;
+    }
+    
+    // Get enum details
+    enum_type_data_t etd;
+    if (!enum_tif.get_enum_details(&etd)) {
+        return false;
+    }
+    
+    // Add new member
+    edm_t member;
+    member.name = qstring(member_name.data(), member_name.size());
+    member.value = value;
+    
+    etd.push_back(member);
+    
+    // Recreate enum with new member
+    tinfo_t new_tif;
+    if (!new_tif.create_enum(etd)) {
+        return false;
+    }
+    
+    // Update the type
+    return check_terr(new_tif.set_numbered_type(til, enum_ordinal, NTF_REPLACE));
+}
+
+// Mark an enum as a bitmask (its members are OR-able flags)
+inline bool set_enum_bitmask(uint32_t enum_ordinal, bool is_bitmask) {
+    til_t* til = get_idati();
+    if (!til) return false;
+    
+    tinfo_t enum_tif;
+    if (!enum_tif.get_numbered_type(til, enum_ordinal)) {
+        return false;
+    }
+    
+    enum_type_data_t etd;
+    if (!enum_tif.get_enum_details(&etd)) {
+        return false;
+    }
+    
+    if (is_bitmask) {
+        etd.bte |= BTE_BITMASK;
+    } else {
+        etd.bte &= ~BTE_BITMASK;
+    }
+    
+    tinfo_t new_tif;
+    if (!new_tif.create_enum(etd)) {
+        return false;
+    }
+    
+    return check_terr(new_tif.set_numbered_type(til, enum_ordinal, NTF_REPLACE));
+}
+
+// Set whether an enum's member values are signed
+inline bool set_enum_signed(uint32_t enum_ordinal, bool is_signed) {
+    til_t* til = get_idati();
+    if (!til) return false;
+    
+    tinfo_t enum_tif;
+    if (!enum_tif.get_numbered_type(til, enum_ordinal)) {
+        return false;
+    }
+    
+    enum_type_data_t etd;
+    if (!enum_tif.get_enum_details(&etd)) {
+        return false;
+    }
+    
+    // Signed enums are shown as signed decimals
+    etd.bte &= ~BTE_OUT_MASK;
+    etd.bte |= is_signed ? BTE_SDEC : BTE_HEX;
+    
+    tinfo_t new_tif;
+    if (!new_tif.create_enum(etd)) {
+        return false;
+    }
+    
+    return check_terr(new_tif.set_numbered_type(til, enum_ordinal, NTF_REPLACE));
+}
+
+// Set how an enum's member values are displayed: `radix` is 2, 8, 10 or 16,
+// or 0 to display them as character constants
+inline bool set_enum_radix(uint32_t enum_ordinal, uint32_t radix, bool is_signed) {
+    til_t* til = get_idati();
+    if (!til) return false;
+    
+    tinfo_t enum_tif;
+    if (!enum_tif.get_numbered_type(til, enum_ordinal)) {
+        return false;
+    }
+    
+    enum_type_data_t etd;
+    if (!enum_tif.get_enum_details(&etd)) {
+        return false;
+    }
+    
+    if (radix == 0) {
+        etd.bte &= ~BTE_OUT_MASK;
+        etd.bte |= BTE_CHAR;
+    } else {
+        etd.set_enum_radix(radix, is_signed);
+    }
+    
+    tinfo_t new_tif;
+    if (!new_tif.create_enum(etd)) {
+        return false;
+    }
+    
+    return check_terr(new_tif.set_numbered_type(til, enum_ordinal, NTF_REPLACE));
+}
+
+// Get the name of the first enum member with the given value, or an empty
+// string if there is none
+inline rust::String get_enum_member_by_value(uint32_t enum_ordinal, int64_t value) {
+    til_t* til = get_idati();
+    if (!til) return rust::String();
+    
+    tinfo_t enum_tif;
+    if (!enum_tif.get_numbered_type(til, enum_ordinal)) {
+        return rust::String();
+    }
+    
+    enum_type_data_t etd;
+    if (!enum_tif.get_enum_details(&etd)) {
+        return rust::String();
+    }
+    
+    for (const edm_t& member : etd) {
+        if (member.value == static_cast<uint64_t>(value)) {
+            return rust::String(member.name.c_str());
+        }
+    }
+    
+    return rust::String();
+}
+
+// ============================================================================
+// Array Type Functions
+// ============================================================================
+
+// Create an array type
+inline uint32_t create_array_type(uint32_t element_type_ordinal, uint32_t num_elements) {
+    til_t* til = get_idati();
+    if (!til) return 0;
+    
+    // Get element type
+    tinfo_t elem_tif;
+    if (!elem_tif.get_numbered_type(til, element_type_ordinal)) {
+        return 0;
+    }
+    
+    // Allocate ordinal
+    uint32_t ordinal = alloc_type_ordinal(til);
+    if (ordinal == 0) return 0;
+    
+    // Create array type
+    tinfo_t tif;
+    array_type_data_t atd;
+    atd.elem_type = elem_tif;
+    atd.nelems = num_elements;
+    
+    if (!tif.create_array(atd)) {
+        return 0;
+    }
+    
+    // Save the array type
+    if (!check_terr(tif.set_numbered_type(til, ordinal, NTF_TYPE))) {
         return 0;
     }
     
     return ordinal;
+}
+
+// ============================================================================
+// Bitfield Type Functions
+// ============================================================================
+
+// Add a bitfield member to a struct
+inline bool add_bitfield_to_struct(
+    uint32_t struct_ordinal,
+    rust::Str field_name,
+    uint32_t bit_offset,
+    uint32_t bit_width,
+    bool is_unsigned,
+    uint32_t storage_bytes
+) {
+    til_t* til = get_idati();
+    if (!til) return false;
+    
+    // Get the struct type
+    tinfo_t struct_tif;
+    if (!struct_tif.get_numbered_type(til, struct_ordinal)) {
+        return false;
+    }
+    
+    // Get existing UDT details
+    udt_type_data_t udt;
+    if (!struct_tif.get_udt_details(&udt)) {
+        return false;
+    }
+    
+    // Create bitfield type for the member
+    // Use the declared storage unit, or calculate the nbytes based on the
+    // offset and width if there is none (0)
+    uint32_t nbytes = storage_bytes;
+    if (nbytes == 0) {
+        uint32_t end_bit = bit_offset + bit_width;
+        nbytes = 1;
+        if (end_bit > 8) nbytes = 2;
+        if (end_bit > 16) nbytes = 4;
+        if (end_bit > 32) nbytes = 8;
+    }
+    
+    tinfo_t bitfield_tif;
+    bitfield_type_data_t bfd(nbytes, bit_width, is_unsigned);
+    if (!bitfield_tif.create_bitfield(bfd)) {
+        return false;
+    }
+    
+    // Add bitfield member
+    udm_t member;
+    member.name = qstring(field_name.data(), field_name.size());
+    member.type = bitfield_tif;
+    member.offset = bit_offset; // Offset in bits
+    member.size = bit_width;    // Size in bits
+    
+    udt.push_back(member);
+    
+    // Recreate the type with the new bitfield
+    tinfo_t new_tif;
+    if (!new_tif.create_udt(udt)) {
+        return false;
+    }
+    
+    // Update the type
+    return check_terr(new_tif.set_numbered_type(til, struct_ordinal, NTF_REPLACE));
+}
+
+// ============================================================================
+// Function Type Functions
+// ============================================================================
+
+// Create a function type
+inline uint32_t create_function_type(
+    uint32_t return_type_ordinal,
+    uint32_t calling_convention,
+    bool is_vararg
+) {
+    til_t* til = get_idati();
+    if (!til) return 0;
+    
+    // Allocate ordinal
+    uint32_t ordinal = alloc_type_ordinal(til);
+    if (ordinal == 0) return 0;
+    
+    // Create function type
+    func_type_data_t ftd;
+    
+    // Set return type (ordinal 0 means void)
+    if (return_type_ordinal != 0) {
+        tinfo_t ret_tif;
+        if (!ret_tif.get_numbered_type(til, return_type_ordinal)) {
+            return 0;
+        }
+        ftd.rettype = ret_tif;
+    } else {
+        ftd.rettype = tinfo_t(BT_VOID);
+    }
+    
+    // Set calling convention
+#if IDA_SDK_VERSION >= 920
+    ftd.set_cc(calling_convention);
+    
+    // Set vararg flag
+    if (is_vararg) {
+        ftd.set_cc(ftd.get_cc() | CM_CC_ELLIPSIS);
+    }
+#else
+    ftd.cc = calling_convention;
+    
+    // Set vararg flag
+    if (is_vararg) {
+        ftd.cc = ftd.cc | CM_CC_ELLIPSIS;
+    }
+#endif
+    
+    // Create the function type
+    tinfo_t tif;
+    if (!tif.create_func(ftd)) {
+        return 0;
+    }
+    
+    // Save the function type
+    if (!check_terr(tif.set_numbered_type(til, ordinal, NTF_TYPE))) {
+        return 0;
+    }
+    
+    return ordinal;
+}
+
+// Add a parameter to a function type
+inline bool add_function_parameter(
+    uint32_t func_ordinal,
+    rust::Str param_name,
+    uint32_t param_type_ordinal,
+    bool is_hidden
+) {
+    til_t* til = get_idati();
+    if (!til) return false;
+    
+    // Get the function type
+    tinfo_t func_tif;
+    if (!func_tif.get_numbered_type(til, func_ordinal)) {
+        return false;
+    }
+    
+    // Get function details
+    func_type_data_t ftd;
+    if (!func_tif.get_func_details(&ftd)) {
+        return false;
+    }
+    
+    // Get parameter type
+    tinfo_t param_tif;
+    if (!param_tif.get_numbered_type(til, param_type_ordinal)) {
+        return false;
+    }
+    
+    // Create new parameter
+    funcarg_t arg;
+    arg.name = qstring(param_name.data(), param_name.size());
+    arg.type = param_tif;
+    if (is_hidden) {
+        arg.flags |= FAI_HIDDEN;
+    }
+    
+    // Add parameter to function
+    ftd.push_back(arg);
+    
+    // Recreate function type with new parameter
+    tinfo_t new_tif;
+    if (!new_tif.create_func(ftd)) {
+        return false;
+    }
+    
+    // Update the type
+    return check_terr(new_tif.set_numbered_type(til, func_ordinal, NTF_REPLACE));
+}
+
+// Add a parameter passed in a register to a function type
+inline bool add_function_parameter_in_register(
+    uint32_t func_ordinal,
+    rust::Str param_name,
+    uint32_t param_type_ordinal,
+    rust::Str reg_name
+) {
+    til_t* til = get_idati();
+    if (!til) return false;
+    
+    // Resolve the register name for the current processor
+    std::string reg_str(reg_name);
+    int reg = str2reg(reg_str.c_str());
+    if (reg < 0) {
+        return false;
+    }
+    
+    tinfo_t func_tif;
+    if (!func_tif.get_numbered_type(til, func_ordinal)) {
+        return false;
+    }
+    
+    func_type_data_t ftd;
+    if (!func_tif.get_func_details(&ftd)) {
+        return false;
+    }
+    
+    tinfo_t param_tif;
+    if (!param_tif.get_numbered_type(til, param_type_ordinal)) {
+        return false;
+    }
+    
+    // Create new parameter with an explicit register location
+    funcarg_t arg;
+    arg.name = qstring(param_name.data(), param_name.size());
+    arg.type = param_tif;
+    arg.argloc.set_reg1(reg);
+    
+    ftd.push_back(arg);
+    
+    tinfo_t new_tif;
+    if (!new_tif.create_func(ftd)) {
+        return false;
+    }
+    
+    return check_terr(new_tif.set_numbered_type(til, func_ordinal, NTF_REPLACE));
+}
+
+// Set function attributes
+inline bool set_function_attributes(
+    uint32_t func_ordinal,
+    bool is_noreturn,
+    bool is_pure,
+    bool is_static,
+    bool is_virtual,
+    bool is_const,
+    bool is_constructor,
+    bool is_destructor
+) {
+    til_t* til = get_idati();
+    if (!til) return false;
+    
+    // Get the function type
+    tinfo_t func_tif;
+    if (!func_tif.get_numbered_type(til, func_ordinal)) {
+        return false;
+    }
+    
+    // Get function details
+    func_type_data_t ftd;
+    if (!func_tif.get_func_details(&ftd)) {
+        return false;
+    }
+    
+    // Set attributes
+    if (is_noreturn) ftd.flags |= FTI_NORET;
+    if (is_pure) ftd.flags |= FTI_PURE;
+    if (is_static) ftd.flags |= FTI_STATIC;
+    if (is_virtual) ftd.flags |= FTI_VIRTUAL;
+    if (is_const) ftd.flags |= FTI_CONST;
+    if (is_constructor) ftd.flags |= FTI_CTOR;
+    if (is_destructor) ftd.flags |= FTI_DTOR;
+    
+    // Recreate function type with new attributes
+    tinfo_t new_tif;
+    if (!new_tif.create_func(ftd)) {
+        return false;
+    }
+    
+    // Update the type
+    return check_terr(new_tif.set_numbered_type(til, func_ordinal, NTF_REPLACE));
+}
+
+// Set the number of stack bytes purged by the callee
+//
+// The purge is not stored as a count: IDA derives it from the stack arguments
+// of conventions where the callee cleans the stack. A function whose
+// convention doesn't purge becomes __userpurge, and the saved type is read
+// back so that a count the arguments don't account for is reported.
+inline bool set_function_purged(uint32_t func_ordinal, uint32_t purged_bytes) {
+    til_t* til = get_idati();
+    if (!til) return false;
+    
+    tinfo_t func_tif;
+    if (!func_tif.get_numbered_type(til, func_ordinal)) {
+        return false;
+    }
+    
+    func_type_data_t ftd;
+    if (!func_tif.get_func_details(&ftd)) {
+        return false;
+    }
+    
+#if IDA_SDK_VERSION >= 920
+    cm_t cc = ftd.get_cc();
+#else
+    cm_t cc = ftd.cc;
+#endif
+    if (!is_purging_cc(cc)) {
+        if (is_vararg_cc(cc)) {
+            last_type_error() = "a vararg function cannot purge its arguments";
+            return false;
+        }
+#if IDA_SDK_VERSION >= 920
+        ftd.set_cc(CM_CC_SPECIALP);
+#else
+        ftd.cc = CM_CC_SPECIALP;
+#endif
+    }
+    ftd.stkargs = purged_bytes;
+    
+    tinfo_t new_tif;
+    if (!new_tif.create_func(ftd)) {
+        return false;
+    }
+    
+    if (!check_terr(new_tif.set_numbered_type(til, func_ordinal, NTF_REPLACE))) {
+        return false;
+    }
+    
+    // Check the purge survived, restoring the previous type if it didn't
+    tinfo_t saved_tif;
+    if (!saved_tif.get_numbered_type(til, func_ordinal)) {
+        return false;
+    }
+    int purged = saved_tif.calc_purged_bytes();
+    if (purged != static_cast<int>(purged_bytes)) {
+        func_tif.set_numbered_type(til, func_ordinal, NTF_REPLACE);
+        last_type_error() = "the stack arguments purge " + std::to_string(purged)
+            + " bytes, not " + std::to_string(purged_bytes);
+        return false;
+    }
+    
+    return true;
+}
+
+// Set the registers spoiled by a function
+inline bool set_function_spoiled(uint32_t func_ordinal, rust::Slice<const rust::String> reg_names) {
+    til_t* til = get_idati();
+    if (!til) return false;
+    
+    tinfo_t func_tif;
+    if (!func_tif.get_numbered_type(til, func_ordinal)) {
+        return false;
+    }
+    
+    func_type_data_t ftd;
+    if (!func_tif.get_func_details(&ftd)) {
+        return false;
+    }
+    
+    // Resolve each register name (and its size) for the current processor
+    for (const auto& name : reg_names) {
+        std::string name_str(name);
+        reg_info_t ri;
+        if (!parse_reg_name(&ri, name_str.c_str())) {
+            return false;
+        }
+        ftd.spoiled.push_back(ri);
+    }
+    ftd.flags |= FTI_SPOILED;
+    
+    tinfo_t new_tif;
+    if (!new_tif.create_func(ftd)) {
+        return false;
+    }
+    
+    return check_terr(new_tif.set_numbered_type(til, func_ordinal, NTF_REPLACE));
+}
+
+// Set the register a function returns its value in
+inline bool set_function_return_register(uint32_t func_ordinal, rust::Str reg_name) {
+    til_t* til = get_idati();
+    if (!til) return false;
+    
+    std::string reg_str(reg_name);
+    int reg = str2reg(reg_str.c_str());
+    if (reg < 0) {
+        return false;
+    }
+    
+    tinfo_t func_tif;
+    if (!func_tif.get_numbered_type(til, func_ordinal)) {
+        return false;
+    }
+    
+    func_type_data_t ftd;
+    if (!func_tif.get_func_details(&ftd)) {
+        return false;
+    }
+    
+    ftd.retloc.set_reg1(reg);
+    
+    tinfo_t new_tif;
+    if (!new_tif.create_func(ftd)) {
+        return false;
+    }
+    
+    return check_terr(new_tif.set_numbered_type(til, func_ordinal, NTF_REPLACE));
+}
+
+// Create a function pointer type
+inline uint32_t create_function_pointer_type(uint32_t func_type_ordinal) {
+    til_t* til = get_idati();
+    if (!til) return 0;
+    
+    // Get the function type
+    tinfo_t func_tif;
+    if (!func_tif.get_numbered_type(til, func_type_ordinal)) {
+        return 0;
+    }
+    
+    // Allocate ordinal for the pointer
+    uint32_t ordinal = alloc_type_ordinal(til);
+    if (ordinal == 0) return 0;
+    
+    // Create pointer to function
+    tinfo_t ptr_tif;
+    ptr_type_data_t ptd;
+    ptd.taptr_bits = 0;
+    ptd.obj_type = func_tif;
+    
+    if (!ptr_tif.create_ptr(ptd)) {
+        return 0;
+    }
+    
+    // Save the function pointer type
+    if (!check_terr(ptr_tif.set_numbered_type(til, ordinal, NTF_TYPE))) {
+        return 0;
+    }
+    
+    return ordinal;
+}
+
+// ============================================================================
+// Pointer Type Functions
+// ============================================================================
+
+// Create a pointer type
+inline uint32_t create_pointer_type(uint32_t target_type_ordinal) {
+    til_t* til = get_idati();
+    if (!til) return 0;
+    
+    // Get target type
+    tinfo_t target_tif;
+    if (!target_tif.get_numbered_type(til, target_type_ordinal)) {
+        return 0;
+    }
+    
+    // Allocate ordinal
+    uint32_t ordinal = alloc_type_ordinal(til);
+    if (ordinal == 0) return 0;
+    
+    // Create pointer type
+    tinfo_t tif;
+    ptr_type_data_t ptd;
+    ptd.taptr_bits = 0; // Standard pointer (no special attributes)
+    ptd.obj_type = target_tif;
+    
+    if (!tif.create_ptr(ptd)) {
+        return 0;
+    }
+    
+    // Save the pointer type
+    if (!check_terr(tif.set_numbered_type(til, ordinal, NTF_TYPE))) {
+        return 0;
+    }
+    
+    return ordinal;
+}
+
+// Create a pointer type with an explicit size in bytes (0 for the default)
+// and memory model (0 for the default, 1 for near, 2 for far)
+inline uint32_t create_sized_pointer_type(
+    uint32_t target_type_ordinal,
+    uint32_t size_bytes,
+    uint32_t model
+) {
+    til_t* til = get_idati();
+    if (!til) return 0;
+    
+    tinfo_t target_tif;
+    if (!target_tif.get_numbered_type(til, target_type_ordinal)) {
+        return 0;
+    }
+    
+    ptr_type_data_t ptd;
+    ptd.taptr_bits = 0;
+    ptd.obj_type = target_tif;
+    
+    // Sizes other than the default are __ptr32/__ptr64 or 16-bit near pointers
+    if (size_bytes != 0 && size_bytes != get_pointer_size()) {
+        switch (size_bytes) {
+            case 2: model = 1; break;
+            case 4: ptd.taptr_bits = TAPTR_PTR32; break;
+            case 8: ptd.taptr_bits = TAPTR_PTR64; break;
+            default: return 0;
+        }
+    }
+    
+    type_t decl_type = BT_PTR;
+    if (model == 1) {
+        decl_type |= BTMT_NEAR;
+    } else if (model == 2) {
+        decl_type |= BTMT_FAR;
+    }
+    
+    tinfo_t tif;
+    if (!tif.create_ptr(ptd, decl_type)) {
+        return 0;
+    }
+    
+    uint32_t ordinal = alloc_type_ordinal(til);
+    if (ordinal == 0) return 0;
+    
+    if (!check_terr(tif.set_numbered_type(til, ordinal, NTF_TYPE))) {
+        return 0;
+    }
+    
+    return ordinal;
+}
+
+// ============================================================================
+// Typedef Functions
+// ============================================================================
+
+// Look up a named type, or create it as a typedef of a primitive type
+inline uint32_t get_or_create_primitive_typedef(rust::Str name, uint32_t bt_type) {
+    std::string name_str(name);
+    til_t* til = get_idati();
+    if (!til) return 0;
+    
+    // Reuse an existing definition with the same name
+    uint32_t existing = get_type_ordinal(til, name_str.c_str());
+    if (existing != 0) return existing;
+    
+    tinfo_t tif;
+    if (!tif.create_simple_type(static_cast<type_t>(bt_type))) {
+        return 0;
+    }
+    
+    uint32_t ordinal = alloc_type_ordinal(til);
+    if (ordinal == 0) return 0;
+    
+    // A named numbered type with a simple definition is a typedef
+    if (!check_terr(tif.set_numbered_type(til, ordinal, NTF_TYPE, name_str.c_str()))) {
+        return 0;
+    }
+    
+    return ordinal;
+}
+
+// Create a named alias of an existing numbered type
+inline uint32_t create_typedef(rust::Str name, uint32_t target_ordinal) {
+    std::string name_str(name);
+    til_t* til = get_idati();
+    if (!til) return 0;
+    
+    tinfo_t target;
+    if (!target.get_numbered_type(til, target_ordinal)) {
+        return 0;
+    }
+    
+    // `typedef struct Foo Foo;` names the type itself
+    const char* target_name = get_numbered_type_name(til, target_ordinal);
+    if (target_name != nullptr && name_str == target_name) {
+        return target_ordinal;
+    }
+    
+    // Named targets are referenced by name, unnamed ones are copied
+    tinfo_t tif;
+    if (target_name != nullptr && target_name[0] != '\0') {
+        if (!tif.create_typedef(til, target_name)) {
+            return 0;
+        }
+    } else {
+        tif = target;
+    }
+    
+    uint32_t ordinal = alloc_type_ordinal(til);
+    if (ordinal == 0) return 0;
+    
+    if (!check_terr(tif.set_numbered_type(til, ordinal, NTF_TYPE, name_str.c_str()))) {
+        return 0;
+    }
+    
+    return ordinal;
+}
+
+// ============================================================================
+// Type Inspection Functions
+// ============================================================================
+
+// Anonymous numbered types of the local type library, loaded on first use,
+// for finding types that have no ordinal of their own
+struct anonymous_types_t {
+    bool loaded = false;
+    std::vector<std::pair<uint32_t, tinfo_t>> types;
+    
+    // Get the ordinal of an anonymous numbered type equal to `tif`, or 0
+    uint32_t find(const tinfo_t& tif) {
+        til_t* til = get_idati();
+        if (!til) return 0;
+        
+        if (!loaded) {
+            loaded = true;
+            uint32_t limit = get_ordinal_limit(til);
+            for (uint32_t i = 1; i < limit; i++) {
+                // Named types resolve to their definition, which is not the
+                // same type
+                const char* name = get_numbered_type_name(til, i);
+                if (name != nullptr && name[0] != '\0') continue;
+                
+                tinfo_t check_tif;
+                if (check_tif.get_numbered_type(til, i)) {
+                    types.emplace_back(i, check_tif);
+                }
+            }
+        }
+        
+        for (const auto& [ordinal, check_tif] : types) {
+            if (tif.equals_to(check_tif)) return ordinal;
+        }
+        return 0;
+    }
+};
+
+// Get the ordinal of a numbered type holding the given type without adding
+// anything to the type library, or 0 if there is none
+inline uint32_t find_numbered_type(const tinfo_t& tif, anonymous_types_t& anonymous) {
+    // Named types and ordinal references already have one, unless qualified
+    uint32_t ordinal = tif.get_ordinal();
+    if (ordinal != 0 && !tif.is_const() && !tif.is_volatile()) return ordinal;
+    
+    return anonymous.find(tif);
+}
+
+inline uint32_t find_numbered_type(const tinfo_t& tif) {
+    anonymous_types_t anonymous;
+    return find_numbered_type(tif, anonymous);
+}
+
+// Get an ordinal holding the given type, reusing an existing one if possible;
+// only for building types, as it adds the type if there is none
+inline uint32_t find_or_create_ordinal(const tinfo_t& tif) {
+    til_t* til = get_idati();
+    if (!til) return 0;
+    
+    uint32_t ordinal = find_numbered_type(tif);
+    if (ordinal != 0) return ordinal;
+    
+    ordinal = alloc_type_ordinal(til);
+    if (ordinal == 0) return 0;
+    
+    if (!check_terr(tif.set_numbered_type(til, ordinal, NTF_TYPE))) {
+        return 0;
+    }
+    
+    return ordinal;
+}
+
+// Get the ordinal of an array's element type, or 0 if it is not an array or
+// the element type has no ordinal
+inline uint32_t get_array_element_ordinal(uint32_t ordinal) {
+    til_t* til = get_idati();
+    if (!til) return 0;
+    
+    tinfo_t tif;
+    array_type_data_t atd;
+    if (!tif.get_numbered_type(til, ordinal) || !tif.get_array_details(&atd)) {
+        return 0;
+    }
+    
+    return find_numbered_type(atd.elem_type);
+}
+
+// Get the number of elements of an array, or -1 if it is not an array
+inline int64_t get_array_length(uint32_t ordinal) {
+    til_t* til = get_idati();
+    if (!til) return -1;
+    
+    tinfo_t tif;
+    array_type_data_t atd;
+    if (!tif.get_numbered_type(til, ordinal) || !tif.get_array_details(&atd)) {
+        return -1;
+    }
+    
+    return atd.nelems;
+}
+
+// Get the ordinal of a named type, or 0 if there is none; types that only
+// exist in a base type library are imported into the local one first
+inline uint32_t get_type_ordinal_by_name(rust::Str name) {
+    std::string name_str(name);
+    til_t* til = get_idati();
+    if (!til || name_str.empty()) return 0;
+    
+    uint32_t ordinal = get_type_ordinal(til, name_str.c_str());
+    if (ordinal != 0) return ordinal;
+    
+    tinfo_t tif;
+    if (!tif.get_named_type(til, name_str.c_str())) {
+        return 0;
+    }
+    
+    if (import_type(til, -1, name_str.c_str()) == BADADDR) {
+        return 0;
+    }
+    
+    return get_type_ordinal(til, name_str.c_str());
+}
+
+// Check whether a type is a pointer
+inline bool is_pointer_type(uint32_t ordinal) {
+    til_t* til = get_idati();
+    if (!til) return false;
+    
+    tinfo_t tif;
+    return tif.get_numbered_type(til, ordinal) && tif.is_ptr();
+}
+
+// Check whether a type is a function prototype
+inline bool is_function_type(uint32_t ordinal) {
+    til_t* til = get_idati();
+    if (!til) return false;
+    
+    tinfo_t tif;
+    return tif.get_numbered_type(til, ordinal) && tif.is_func();
+}
+
+// Get the ordinal of the type a pointer points to, or 0 if it is not a pointer
+// or the target type has no ordinal
+inline uint32_t get_pointer_target(uint32_t ordinal) {
+    til_t* til = get_idati();
+    if (!til) return 0;
+    
+    tinfo_t tif;
+    if (!tif.get_numbered_type(til, ordinal) || !tif.is_ptr()) {
+        return 0;
+    }
+    
+    return find_numbered_type(tif.get_pointed_object());
+}
+
+// Render a type as a C declaration, printing the full body of structs,
+// unions and enums. Returns an empty string on failure
+inline rust::String print_type_decl(uint32_t ordinal) {
+    til_t* til = get_idati();
+    if (!til) return rust::String();
+    
+    tinfo_t tif;
+    if (!tif.get_numbered_type(til, ordinal)) {
+        return rust::String();
+    }
+    
+    qstring out;
+    int flags = PRTYPE_MULTI | PRTYPE_TYPE | PRTYPE_SEMI | PRTYPE_DEF;
+    if (!tif.print(&out, nullptr, flags)) {
+        return rust::String();
+    }
+    
+    return rust::String(out.c_str());
+}
+
+// ============================================================================
+// Type Qualifier Functions
+// ============================================================================
+
+// Get the ordinal of a const and/or volatile qualified version of a type
+inline uint32_t create_qualified_type(uint32_t ordinal, bool is_const, bool is_volatile) {
+    til_t* til = get_idati();
+    if (!til) return 0;
+    
+    tinfo_t tif;
+    if (!tif.get_numbered_type(til, ordinal)) {
+        return 0;
+    }
+    
+    if (is_const) tif.set_const();
+    if (is_volatile) tif.set_volatile();
+    
+    return find_or_create_ordinal(tif);
+}
+
+// ============================================================================
+// Type Management Functions
+// ============================================================================
+
+// Check whether a type refers to the type with the given ordinal, looking
+// through pointers, arrays, members and function signatures but not into
+// other named types
+inline bool tinfo_refers_to(const tinfo_t& tif, uint32_t ordinal, int depth) {
+    if (depth > 0) {
+        uint32_t referenced = tif.get_ordinal();
+        if (referenced != 0) return referenced == ordinal;
+    }
+    if (depth > 32) return false;
+    
+    if (tif.is_typedef()) {
+        qstring next_name;
+        return tif.get_next_type_name(&next_name)
+            && get_type_ordinal(get_idati(), next_name.c_str()) == ordinal;
+    }
+    if (tif.is_ptr()) {
+        return tinfo_refers_to(tif.get_pointed_object(), ordinal, depth + 1);
+    }
+    if (tif.is_array()) {
+        return tinfo_refers_to(tif.get_array_element(), ordinal, depth + 1);
+    }
+    
+    udt_type_data_t udt;
+    if (tif.get_udt_details(&udt)) {
+        for (const udm_t& member : udt) {
+            if (tinfo_refers_to(member.type, ordinal, depth + 1)) return true;
+        }
+        return false;
+    }
+    
+    func_type_data_t ftd;
+    if (tif.get_func_details(&ftd)) {
+        if (tinfo_refers_to(ftd.rettype, ordinal, depth + 1)) return true;
+        for (const funcarg_t& arg : ftd) {
+            if (tinfo_refers_to(arg.type, ordinal, depth + 1)) return true;
+        }
+    }
+    
+    return false;
+}
+
+// Get the name of a named type that refers to the given type, or an empty
+// string if there is none
+inline rust::String find_type_referrer(uint32_t ordinal) {
+    til_t* til = get_idati();
+    if (!til) return rust::String();
+    
+    uint32_t limit = get_ordinal_limit(til);
+    for (uint32_t i = 1; i < limit; i++) {
+        if (i == ordinal) continue;
+        
+        // Unnamed types are intermediate pointers/arrays of named ones
+        const char* name = get_numbered_type_name(til, i);
+        if (name == nullptr || name[0] == '\0') continue;
+        
+        tinfo_t tif;
+        if (tif.get_numbered_type(til, i) && tinfo_refers_to(tif, ordinal, 0)) {
+            return rust::String(name);
+        }
+    }
+    
+    return rust::String();
+}
+
+// Delete a type from the local type library
+inline bool delete_type(uint32_t ordinal) {
+    til_t* til = get_idati();
+    if (!til) return false;
+    
+    return del_numbered_type(til, ordinal);
+}
+
+// Check whether a type with the given name exists in the local type library
+// or one of its base libraries
+inline bool type_name_exists(rust::Str name) {
+    std::string name_str(name);
+    til_t* til = get_idati();
+    if (!til) return false;
+    
+    tinfo_t tif;
+    return tif.get_named_type(til, name_str.c_str());
+}
+
+// Give a type a new name; references to it are by ordinal and follow along
+inline bool rename_type(uint32_t ordinal, rust::Str new_name) {
+    std::string name_str(new_name);
+    til_t* til = get_idati();
+    if (!til || name_str.empty()) return false;
+    
+    tinfo_t tif;
+    if (!tif.get_numbered_type(til, ordinal)) {
+        return false;
+    }
+    
+    return check_terr(tif.set_numbered_type(til, ordinal, NTF_REPLACE, name_str.c_str()));
+}
+
+// ============================================================================
+// Type Parsing Functions
+// ============================================================================
+
+// Parse a single C declaration and save it as a named type, replacing any
+// existing type of the same name. Returns the ordinal, or 0 on failure
+inline uint32_t parse_type_decl(rust::Str decl) {
+    std::string decl_str(decl);
+    til_t* til = get_idati();
+    if (!til) return 0;
+    
+    // IDA requires declarations to be terminated
+    size_t end = decl_str.find_last_not_of(" \t\r\n");
+    if (end == std::string::npos) return 0;
+    if (decl_str[end] != ';') decl_str += ';';
+    
+    tinfo_t tif;
+    qstring name;
+    if (!parse_decl(&tif, &name, til, decl_str.c_str(), PT_SIL | PT_TYP)) {
+        return 0;
+    }
+    
+    // Anonymous declarations have nothing to save the type under
+    if (name.empty()) return 0;
+    
+    if (!check_terr(tif.set_named_type(til, name.c_str(), NTF_REPLACE))) {
+        return 0;
+    }
+    
+    return get_type_ordinal(til, name.c_str());
+}
+
+// Parse a preprocessed C header and return the ordinals of the named types it
+// created, in the order they were declared. The number of parse errors is
+// stored in error_count
+inline rust::Vec<uint32_t> parse_header_types(rust::Str source, int32_t& error_count) {
+    rust::Vec<uint32_t> ordinals;
+    std::string source_str(source);
+    til_t* til = get_idati();
+    if (!til) {
+        error_count = -1;
+        return ordinals;
+    }
+    
+    uint32_t first = get_ordinal_limit(til);
+    
+    // Input is text; HTI_DCL = don't complain about redeclarations,
+    // HTI_NWR = no warnings
+    error_count = parse_decls(til, source_str.c_str(), nullptr, HTI_DCL | HTI_NWR);
+    
+    uint32_t limit = get_ordinal_limit(til);
+    for (uint32_t i = first; i < limit; i++) {
+        const char* name = get_numbered_type_name(til, i);
+        if (name != nullptr && name[0] != '\0') {
+            ordinals.push_back(i);
+        }
+    }
+    
+    return ordinals;
+}
+
+// ============================================================================
+// Type Application Functions
+// ============================================================================
+
+// Apply a type to a data address. Returns 0 on success, 1 if the type does
+// not exist or has no size, 2 if the range is not fully mapped, 3 if the
+// range overlaps an instruction, and -1 if IDA refuses the type
+inline int32_t apply_type_to_address(uint64_t ea, uint32_t ordinal) {
+    til_t* til = get_idati();
+    if (!til) return 1;
+    
+    tinfo_t tif;
+    if (!tif.get_numbered_type(til, ordinal)) {
+        return 1;
+    }
+    
+    size_t size = tif.get_size();
+    if (size == BADSIZE || size == 0) {
+        return 1;
+    }
+    
+    ea_t end = ea + size;
+    for (ea_t cur = ea; cur < end; cur++) {
+        if (!is_mapped(cur)) return 2;
+    }
+    
+    // Data items in the range are replaced, but instructions are kept
+    for (ea_t head = get_item_head(ea); head != BADADDR && head < end; head = next_head(head, end)) {
+        if (is_code(get_flags(head))) return 3;
+    }
+    
+    return apply_tinfo(ea, tif, TINFO_DEFINITE) ? 0 : -1;
+}
+
+// Apply a function prototype to the function starting at an address
+inline bool apply_function_type(uint64_t func_ea, uint32_t ordinal) {
+    til_t* til = get_idati();
+    if (!til) return false;
+    
+    tinfo_t tif;
+    if (!tif.get_numbered_type(til, ordinal) || !tif.is_func()) {
+        return false;
+    }
+    
+    return apply_tinfo(func_ea, tif, TINFO_DEFINITE);
+}
+
+// ============================================================================
+// Type Introspection Functions
+// ============================================================================
+
+// Get the name of a type, or an empty string if it has none
+inline rust::String get_type_name(uint32_t ordinal) {
+    til_t* til = get_idati();
+    if (!til) return rust::String();
+    
+    const char* name = get_numbered_type_name(til, ordinal);
+    return rust::String(name != nullptr ? name : "");
+}
+
+// Check whether a named type also exists in one of the base type libraries,
+// i.e. it comes from a standard library rather than being defined locally
+inline bool is_library_type(uint32_t ordinal) {
+    til_t* til = get_idati();
+    if (!til) return false;
+    
+    const char* name = get_numbered_type_name(til, ordinal);
+    if (name == nullptr || name[0] == '\0') return false;
+    
+    for (int i = 0; i < til->nbases; i++) {
+        if (get_named_type(til->base[i], name, NTF_TYPE) != 0) return true;
+    }
+    return false;
+}
+
+// Get the basic type code of an unqualified primitive type, or 0 if the type
+// is not one
+inline uint32_t simple_type_code(const tinfo_t& tif) {
+    type_t decl = tif.get_decltype();
+    if ((decl & TYPE_MODIF_MASK) != 0) return 0;
+    
+    tinfo_t simple;
+    if (!simple.create_simple_type(decl) || !simple.equals_to(tif)) {
+        return 0;
+    }
+    
+    return decl;
+}
+
+// Get the basic type code of a numbered primitive type, or 0 if the type is
+// not one
+inline uint32_t get_primitive_type_code(uint32_t ordinal) {
+    til_t* til = get_idati();
+    if (!til) return 0;
+    
+    tinfo_t tif;
+    if (!tif.get_numbered_type(til, ordinal)) {
+        return 0;
+    }
+    return simple_type_code(tif);
+}
+
+// Get the definition of a numbered type rather than a reference to it, so
+// typedefs are not resolved
+inline bool get_type_definition(til_t* til, uint32_t ordinal, tinfo_t* tif) {
+    const type_t* type = nullptr;
+    const p_list* fields = nullptr;
+    if (!get_numbered_type(til, ordinal, &type, &fields)) {
+        return false;
+    }
+    return tif->deserialize(til, &type, &fields);
+}
+
+// Classify a type: 1 primitive, 2 struct, 3 union, 4 enum, 5 pointer,
+// 6 array, 7 function, 8 typedef, or 0 if it does not exist
+inline uint32_t get_type_kind(uint32_t ordinal) {
+    til_t* til = get_idati();
+    if (!til) return 0;
+    
+    tinfo_t tif;
+    if (!get_type_definition(til, ordinal, &tif)) {
+        return 0;
+    }
+    
+    if (tif.is_udt()) return tif.is_union() ? 3 : 2;
+    if (tif.is_enum()) return 4;
+    if (tif.is_func()) return 7;
+    
+    // Named types that are not structs, unions, enums or functions alias
+    // their definition
+    const char* name = get_numbered_type_name(til, ordinal);
+    if ((name != nullptr && name[0] != '\0') || tif.is_typedef()) return 8;
+    
+    if (tif.is_ptr()) return 5;
+    if (tif.is_array()) return 6;
+    return 1;
+}
+
+// Check whether two types have the same definition, ignoring their names
+inline bool compare_types(uint32_t lhs_ordinal, uint32_t rhs_ordinal) {
+    til_t* til = get_idati();
+    if (!til) return false;
+    
+    tinfo_t lhs;
+    tinfo_t rhs;
+    if (!get_type_definition(til, lhs_ordinal, &lhs) || !get_type_definition(til, rhs_ordinal, &rhs)) {
+        return false;
+    }
+    
+    return lhs.compare_with(rhs, TCMP_EQUAL);
+}
+
+// Get the definition of a typedef, or false if the type is a struct, union,
+// enum or function rather than a typedef
+inline bool get_typedef_definition(uint32_t ordinal, tinfo_t* tif) {
+    til_t* til = get_idati();
+    if (!til || !get_type_definition(til, ordinal, tif)) {
+        return false;
+    }
+    return !tif->is_udt() && !tif->is_enum() && !tif->is_func();
+}
+
+// Get the ordinal of the type a typedef aliases, or 0 if the type is not a
+// typedef or the aliased type has no ordinal
+inline uint32_t get_typedef_target(uint32_t ordinal) {
+    tinfo_t tif;
+    if (!get_typedef_definition(ordinal, &tif)) {
+        return 0;
+    }
+    return find_numbered_type(tif);
+}
+
+// Get the members of a struct/union, or false if the type is not one
+inline bool get_udt_members(uint32_t ordinal, udt_type_data_t* udt) {
+    til_t* til = get_idati();
+    if (!til) return false;
+    
+    tinfo_t tif;
+    return tif.get_numbered_type(til, ordinal) && tif.get_udt_details(udt);
+}
+
+// Get the number of members of a struct/union, or -1 if the type is not one
+inline int32_t get_udt_member_count(uint32_t ordinal) {
+    udt_type_data_t udt;
+    if (!get_udt_members(ordinal, &udt)) return -1;
+    return static_cast<int32_t>(udt.size());
+}
+
+// Check whether a type is a union
+inline bool is_union_type(uint32_t ordinal) {
+    udt_type_data_t udt;
+    return get_udt_members(ordinal, &udt) && udt.is_union;
+}
+
+// Get the name of a struct/union member
+inline rust::String get_udt_member_name(uint32_t ordinal, uint32_t index) {
+    udt_type_data_t udt;
+    if (!get_udt_members(ordinal, &udt) || index >= udt.size()) {
+        return rust::String();
+    }
+    return rust::String(udt[index].name.c_str());
+}
+
+// Get the ordinal of the type of a struct/union member, or 0 on failure or
+// if the member type has no ordinal
+inline uint32_t get_udt_member_type(uint32_t ordinal, uint32_t index) {
+    udt_type_data_t udt;
+    if (!get_udt_members(ordinal, &udt) || index >= udt.size()) {
+        return 0;
+    }
+    return find_numbered_type(udt[index].type);
+}
+
+// Get the offset of a struct/union member in bits
+inline uint64_t get_udt_member_offset(uint32_t ordinal, uint32_t index) {
+    udt_type_data_t udt;
+    if (!get_udt_members(ordinal, &udt) || index >= udt.size()) {
+        return 0;
+    }
+    return udt[index].offset;
+}
+
+// Get the size of a struct/union member in bits
+inline uint64_t get_udt_member_size(uint32_t ordinal, uint32_t index) {
+    udt_type_data_t udt;
+    if (!get_udt_members(ordinal, &udt) || index >= udt.size()) {
+        return 0;
+    }
+    return udt[index].size;
+}
+
+// Get whether a struct/union member is a bitfield: -1 if it is not, 0 for a
+// signed bitfield and 1 for an unsigned one
+inline int32_t get_udt_member_bitfield(uint32_t ordinal, uint32_t index) {
+    udt_type_data_t udt;
+    if (!get_udt_members(ordinal, &udt) || index >= udt.size()) {
+        return -1;
+    }
+    
+    bitfield_type_data_t bfd;
+    if (!udt[index].type.get_bitfield_details(&bfd)) {
+        return -1;
+    }
+    return bfd.is_unsigned ? 1 : 0;
+}
+
+// Get the size in bytes of the storage unit of a bitfield member, or 0 if the
+// member is not a bitfield
+inline uint32_t get_udt_member_bitfield_storage(uint32_t ordinal, uint32_t index) {
+    udt_type_data_t udt;
+    if (!get_udt_members(ordinal, &udt) || index >= udt.size()) {
+        return 0;
+    }
+    
+    bitfield_type_data_t bfd;
+    if (!udt[index].type.get_bitfield_details(&bfd)) {
+        return 0;
+    }
+    return bfd.nbytes;
+}
+
+// Get the names of all members of a struct/union, empty if it is not one
+inline rust::Vec<rust::String> get_udt_member_names(uint32_t ordinal) {
+    rust::Vec<rust::String> names;
+    udt_type_data_t udt;
+    if (get_udt_members(ordinal, &udt)) {
+        for (const udm_t& udm : udt) {
+            names.push_back(rust::String(udm.name.c_str()));
+        }
+    }
+    return names;
+}
+
+// Get the ordinals of the types of all members of a struct/union, 0 for
+// member types without an ordinal; empty if it is not a struct/union
+inline rust::Vec<uint32_t> get_udt_member_types(uint32_t ordinal) {
+    rust::Vec<uint32_t> types;
+    udt_type_data_t udt;
+    if (get_udt_members(ordinal, &udt)) {
+        anonymous_types_t anonymous;
+        for (const udm_t& udm : udt) {
+            types.push_back(find_numbered_type(udm.type, anonymous));
+        }
+    }
+    return types;
+}
+
+// Get the offsets in bits of all members of a struct/union
+inline rust::Vec<uint64_t> get_udt_member_offsets(uint32_t ordinal) {
+    rust::Vec<uint64_t> offsets;
+    udt_type_data_t udt;
+    if (get_udt_members(ordinal, &udt)) {
+        for (const udm_t& udm : udt) {
+            offsets.push_back(udm.offset);
+        }
+    }
+    return offsets;
+}
+
+// Get the sizes in bits of all members of a struct/union
+inline rust::Vec<uint64_t> get_udt_member_sizes(uint32_t ordinal) {
+    rust::Vec<uint64_t> sizes;
+    udt_type_data_t udt;
+    if (get_udt_members(ordinal, &udt)) {
+        for (const udm_t& udm : udt) {
+            sizes.push_back(udm.size);
+        }
+    }
+    return sizes;
+}
+
+// Get whether each member of a struct/union is a bitfield, like
+// get_udt_member_bitfield
+inline rust::Vec<int32_t> get_udt_member_bitfields(uint32_t ordinal) {
+    rust::Vec<int32_t> bitfields;
+    udt_type_data_t udt;
+    if (get_udt_members(ordinal, &udt)) {
+        for (const udm_t& udm : udt) {
+            bitfield_type_data_t bfd;
+            if (!udm.type.get_bitfield_details(&bfd)) {
+                bitfields.push_back(-1);
+            } else {
+                bitfields.push_back(bfd.is_unsigned ? 1 : 0);
+            }
+        }
+    }
+    return bitfields;
+}
+
+// Get the comments of all members of a struct/union as stored, empty for
+// members without one; set back as repeatable comments they are unchanged
+inline rust::Vec<rust::String> get_udt_member_comments(uint32_t ordinal) {
+    rust::Vec<rust::String> comments;
+    udt_type_data_t udt;
+    if (get_udt_members(ordinal, &udt)) {
+        for (const udm_t& udm : udt) {
+            comments.push_back(rust::String(udm.cmt.c_str()));
+        }
+    }
+    return comments;
+}
+
+// Get the role of each member of a struct/union: 1 for a base class, 2 for
+// the pointer to the class's vtable, or 0 for a plain member
+inline rust::Vec<uint32_t> get_udt_member_roles(uint32_t ordinal) {
+    rust::Vec<uint32_t> roles;
+    udt_type_data_t udt;
+    if (get_udt_members(ordinal, &udt)) {
+        for (const udm_t& udm : udt) {
+            roles.push_back(udm.is_baseclass() ? 1 : udm.is_vftable() ? 2 : 0);
+        }
+    }
+    return roles;
+}
+
+// Get the layout settings of a struct/union: the packing and declared
+// alignment in bytes (0 when not set), then 1 if tail padding is off and 2 if
+// it is a C++ object, OR-ed together; empty if it is not a struct/union
+inline rust::Vec<uint32_t> get_udt_layout(uint32_t ordinal) {
+    rust::Vec<uint32_t> layout;
+    udt_type_data_t udt;
+    if (!get_udt_members(ordinal, &udt)) {
+        return layout;
+    }
+    // Both are stored as shift amounts; sda is offset by one (0 = unspecified)
+    layout.push_back(udt.pack != 0 ? 1u << udt.pack : 0);
+    layout.push_back(udt.sda != 0 ? 1u << (udt.sda - 1) : 0);
+    layout.push_back((udt.is_unaligned() ? 1 : 0) | (udt.is_cppobj() ? 2 : 0));
+    return layout;
+}
+
+// Describe a type without adding anything to the type library, as steps from
+// the outside in: any number of "pointer" and "array <count>", then
+// "ordinal <ordinal>" for a numbered type, "primitive <code>" for a basic
+// type, or "decl <expr>" with a C type expression for anything else
+inline rust::Vec<rust::String> describe_tinfo(tinfo_t tif) {
+    rust::Vec<rust::String> steps;
+    anonymous_types_t anonymous;
+    for (;;) {
+        bool qualified = tif.is_const() || tif.is_volatile();
+        uint32_t ordinal = tif.get_ordinal();
+        if (ordinal != 0 && !qualified) {
+            steps.push_back(rust::String("ordinal " + std::to_string(ordinal)));
+            break;
+        }
+        
+        // Sized and based pointers are described by their declaration
+        if (!qualified && tif.is_ptr() && tif.get_size() == get_pointer_size()) {
+            steps.push_back(rust::String("pointer"));
+            tif = tif.get_pointed_object();
+            continue;
+        }
+        
+        array_type_data_t atd;
+        if (!qualified && tif.get_array_details(&atd)) {
+            steps.push_back(rust::String("array " + std::to_string(atd.nelems)));
+            tif = atd.elem_type;
+            continue;
+        }
+        
+        if (uint32_t code = simple_type_code(tif); code != 0) {
+            steps.push_back(rust::String("primitive " + std::to_string(code)));
+        } else if (ordinal = anonymous.find(tif); ordinal != 0) {
+            steps.push_back(rust::String("ordinal " + std::to_string(ordinal)));
+        } else {
+            qstring expr;
+            tif.print(&expr);
+            steps.push_back(rust::String(std::string("decl ") + expr.c_str()));
+        }
+        break;
+    }
+    return steps;
+}
+
+// Describe the type of a struct/union member (see describe_tinfo), or get no
+// steps on failure
+inline rust::Vec<rust::String> describe_udt_member_type(uint32_t ordinal, uint32_t index) {
+    udt_type_data_t udt;
+    if (!get_udt_members(ordinal, &udt) || index >= udt.size()) {
+        return rust::Vec<rust::String>();
+    }
+    return describe_tinfo(udt[index].type);
+}
+
+// Describe the type a typedef aliases (see describe_tinfo), or get no steps
+// if the type is not a typedef
+inline rust::Vec<rust::String> describe_typedef_target(uint32_t ordinal) {
+    tinfo_t tif;
+    if (!get_typedef_definition(ordinal, &tif)) {
+        return rust::Vec<rust::String>();
+    }
+    return describe_tinfo(tif);
+}
+
+// Get the members of an enum, or false if the type is not one
+inline bool get_enum_members(uint32_t ordinal, enum_type_data_t* etd) {
+    til_t* til = get_idati();
+    if (!til) return false;
+    
+    tinfo_t tif;
+    return tif.get_numbered_type(til, ordinal) && tif.get_enum_details(etd);
+}
+
+// Get the number of members of an enum, or -1 if the type is not one
+inline int32_t get_enum_member_count(uint32_t ordinal) {
+    enum_type_data_t etd;
+    if (!get_enum_members(ordinal, &etd)) return -1;
+    return static_cast<int32_t>(etd.size());
+}
+
+// Get the name of an enum member
+inline rust::String get_enum_member_name(uint32_t ordinal, uint32_t index) {
+    enum_type_data_t etd;
+    if (!get_enum_members(ordinal, &etd) || index >= etd.size()) {
+        return rust::String();
+    }
+    return rust::String(etd[index].name.c_str());
+}
+
+// Get the value of an enum member
+inline int64_t get_enum_member_value(uint32_t ordinal, uint32_t index) {
+    enum_type_data_t etd;
+    if (!get_enum_members(ordinal, &etd) || index >= etd.size()) {
+        return 0;
+    }
+    return static_cast<int64_t>(etd[index].value);
+}
+
+// Check whether an enum is a bitmask
+inline bool is_bitmask_enum(uint32_t ordinal) {
+    enum_type_data_t etd;
+    return get_enum_members(ordinal, &etd) && etd.is_bf();
+}
+
+// Render a type as a one-line C type expression, like `char[16]`
+inline rust::String print_type_expr(uint32_t ordinal) {
+    til_t* til = get_idati();
+    if (!til) return rust::String();
+    
+    tinfo_t tif;
+    qstring out;
+    if (!tif.get_numbered_type(til, ordinal) || !tif.print(&out)) {
+        return rust::String();
+    }
+    return rust::String(out.c_str());
+}
+
+// Parse a C type expression, like `char[16]`, and get the ordinal of the
+// type, or 0 on failure
+inline uint32_t parse_type_expr(rust::Str expr) {
+    std::string decl_str(expr);
+    til_t* til = get_idati();
+    if (!til || decl_str.empty()) return 0;
+    decl_str += ';';
+    
+    tinfo_t tif;
+    qstring name;
+    if (!parse_decl(&tif, &name, til, decl_str.c_str(), PT_SIL)) {
+        return 0;
+    }
+    return find_or_create_ordinal(tif);
+}
+
+// ============================================================================
+// Compiler Functions
+// ============================================================================
+
+// Set the compiler and default calling convention (a CM_CC_* value) of the
+// database, keeping its memory model and type sizes. The default alignment
+// was chosen for the old compiler, so it is reset to unspecified (natural
+// alignment under the new compiler's layout rules)
+inline bool set_database_compiler(uint32_t compiler_id, uint32_t cc) {
+    compiler_info_t info;
+    if (!inf_get_cc(&info)) {
+        last_type_error() = "cannot read the compiler of the database";
+        return false;
+    }
+    
+    info.id = comp_t(compiler_id);
+    info.cm = cm_t((info.cm & ~CM_CC_MASK) | (cc & CM_CC_MASK));
+    info.defalign = 0;
+    if (!set_compiler(info, SETCOMP_OVERRIDE)) {
+        last_type_error() = "IDA rejected the compiler settings";
+        return false;
+    }
+    return true;
+}
+
+// ============================================================================
+// Name Demangling Functions
+// ============================================================================
+
+// Demangle a C++ symbol name in the database's short or long form; empty if
+// it is not a mangled name
+inline rust::String demangle_symbol(rust::Str mangled, bool short_form) {
+    std::string mangled_str(mangled);
+    if (mangled_str.empty()) return rust::String();
+    
+    uint32 disable_mask = short_form ? inf_get_short_demnames() : inf_get_long_demnames();
+    qstring out;
+    if (demangle_name(&out, mangled_str.c_str(), disable_mask, DQT_FULL) <= 0) {
+        return rust::String();
+    }
+    return rust::String(out.c_str());
 }
\ No newline at end of file
@@ -8,27 +8,62 @@ pub mod ffi_types {
         // Type creation functions
         fn create_struct_type(name: &str) -> u32;
         fn create_union_type(name: &str) -> u32;
+        fn forward_declare_struct(name: &str) -> u32;
         fn add_field_to_type(
             type_ordinal: u32,
             field_name: &str,
             field_type_ordinal: u32,
             offset: u64,
         ) -> bool;
+        fn add_baseclass(type_ordinal: u32, field_name: &str, base_ordinal: u32) -> bool;
+        fn set_field_comment(
+            type_ordinal: u32,
+            field_name: &str,
+            comment: &str,
+            repeatable: bool,
+        ) -> bool;
+        fn set_udt_kind(type_ordinal: u32, is_union: bool) -> bool;
+        fn clear_udt_members(type_ordinal: u32) -> bool;
+        fn reset_to_empty_udt(type_ordinal: u32, is_union: bool) -> bool;
+        fn save_type_definition(type_ordinal: u32) -> Vec<u8>;
+        fn restore_type_definition(type_ordinal: u32, saved: &[u8]) -> bool;
         fn finalize_type(type_ordinal: u32) -> bool;
+        fn set_type_alignment(type_ordinal: u32, pack: u32, align: u32) -> bool;
+        fn set_udt_unaligned(type_ordinal: u32, unaligned: bool) -> bool;
+        fn set_udt_cpp_object(type_ordinal: u32, cpp_object: bool) -> bool;
+        fn set_udt_vftable(type_ordinal: u32, vftable: bool) -> bool;
+        fn set_udt_member_vftable(type_ordinal: u32, field_name: &str) -> bool;
+        fn set_udt_member_type(
+            type_ordinal: u32,
+            field_name: &str,
+            member_type_ordinal: u32,
+            may_grow: bool,
+        ) -> bool;
+        fn rename_udt_member(type_ordinal: u32, old_name: &str, new_name: &str) -> bool;
         
         // Helper functions
         fn get_primitive_type_ordinal(bt_type: u32) -> u32;
         fn get_type_size(ordinal: u32) -> u64;
+        fn get_type_alignment(ordinal: u32) -> u64;
+        fn take_last_type_error() -> String;
+        fn get_pointer_size() -> u32;
+        fn get_wchar_size() -> u32;
         
         // Enum type functions
         fn create_enum_type(name: &str, width: u32) -> u32;
         fn add_enum_member(enum_ordinal: u32, member_name: &str, value: i64) -> bool;
+        fn set_enum_bitmask(enum_ordinal: u32, is_bitmask: bool) -> bool;
+        fn set_enum_signed(enum_ordinal: u32, is_signed: bool) -> bool;
+        fn get_enum_member_by_value(enum_ordinal: u32, value: i64) -> String;
+        fn set_enum_radix(enum_ordinal: u32, radix: u32, is_signed: bool) -> bool;
         
         // Array type functions
         fn create_array_type(element_type_ordinal: u32, num_elements: u32) -> u32;
         
         // Pointer type functions
+        fn create_qualified_type(ordinal: u32, is_const: bool, is_volatile: bool) -> u32;
         fn create_pointer_type(target_type_ordinal: u32) -> u32;
+        fn create_sized_pointer_type(target_type_ordinal: u32, size_bytes: u32, model: u32) -> u32;
         
         // Bitfield type functions
         fn add_bitfield_to_struct(
@@ -37,6 +72,7 @@ pub mod ffi_types {
             bit_offset: u32,
             bit_width: u32,
             is_unsigned: bool,
+            storage_bytes: u32,
         ) -> bool;
         
         // Function type functions
@@ -51,6 +87,12 @@ pub mod ffi_types {
             param_type_ordinal: u32,
             is_hidden: bool,
         ) -> bool;
+        fn add_function_parameter_in_register(
+            func_ordinal: u32,
+            param_name: &str,
+            param_type_ordinal: u32,
+            reg_name: &str,
+        ) -> bool;
         fn set_function_attributes(
             func_ordinal: u32,
             is_noreturn: bool,
@@ -61,6 +103,74 @@ pub mod ffi_types {
             is_constructor: bool,
             is_destructor: bool,
         ) -> bool;
+        fn set_function_purged(func_ordinal: u32, purged_bytes: u32) -> bool;
+        fn set_function_spoiled(func_ordinal: u32, reg_names: &[String]) -> bool;
+        fn set_function_return_register(func_ordinal: u32, reg_name: &str) -> bool;
         fn create_function_pointer_type(func_type_ordinal: u32) -> u32;
+        
+        // Typedef functions
+        fn get_or_create_primitive_typedef(name: &str, bt_type: u32) -> u32;
+        fn create_typedef(name: &str, target_ordinal: u32) -> u32;
+        
+        // Type inspection functions
+        fn get_array_element_ordinal(ordinal: u32) -> u32;
+        fn get_array_length(ordinal: u32) -> i64;
+        fn is_pointer_type(ordinal: u32) -> bool;
+        fn is_function_type(ordinal: u32) -> bool;
+        fn get_pointer_target(ordinal: u32) -> u32;
+        fn get_type_ordinal_by_name(name: &str) -> u32;
+        fn print_type_decl(ordinal: u32) -> String;
+        
+        // Type management functions
+        fn find_type_referrer(ordinal: u32) -> String;
+        fn delete_type(ordinal: u32) -> bool;
+        fn type_name_exists(name: &str) -> bool;
+        fn rename_type(ordinal: u32, new_name: &str) -> bool;
+        
+        // Type parsing functions
+        fn parse_type_decl(decl: &str) -> u32;
+        fn parse_header_types(source: &str, error_count: &mut i32) -> Vec<u32>;
+
+        // Compiler functions
+        fn set_database_compiler(compiler_id: u32, cc: u32) -> bool;
+
+        // Name demangling functions
+        fn demangle_symbol(mangled: &str, short_form: bool) -> String;
+        
+        // Type application functions
+        fn apply_type_to_address(ea: u64, ordinal: u32) -> i32;
+        fn apply_function_type(func_ea: u64, ordinal: u32) -> bool;
+        
+        // Type introspection functions
+        fn get_type_name(ordinal: u32) -> String;
+        fn is_library_type(ordinal: u32) -> bool;
+        fn get_primitive_type_code(ordinal: u32) -> u32;
+        fn get_typedef_target(ordinal: u32) -> u32;
+        fn compare_types(lhs_ordinal: u32, rhs_ordinal: u32) -> bool;
+        fn get_type_kind(ordinal: u32) -> u32;
+        fn get_udt_member_count(ordinal: u32) -> i32;
+        fn is_union_type(ordinal: u32) -> bool;
+        fn get_udt_member_name(ordinal: u32, index: u32) -> String;
+        fn get_udt_member_type(ordinal: u32, index: u32) -> u32;
+        fn get_udt_member_offset(ordinal: u32, index: u32) -> u64;
+        fn get_udt_member_size(ordinal: u32, index: u32) -> u64;
+        fn get_udt_member_bitfield(ordinal: u32, index: u32) -> i32;
+        fn get_udt_member_bitfield_storage(ordinal: u32, index: u32) -> u32;
+        fn get_udt_member_names(ordinal: u32) -> Vec<String>;
+        fn get_udt_member_types(ordinal: u32) -> Vec<u32>;
+        fn get_udt_member_offsets(ordinal: u32) -> Vec<u64>;
+        fn get_udt_member_sizes(ordinal: u32) -> Vec<u64>;
+        fn get_udt_member_bitfields(ordinal: u32) -> Vec<i32>;
+        fn get_udt_member_comments(ordinal: u32) -> Vec<String>;
+        fn get_udt_member_roles(ordinal: u32) -> Vec<u32>;
+        fn get_udt_layout(ordinal: u32) -> Vec<u32>;
+        fn describe_udt_member_type(ordinal: u32, index: u32) -> Vec<String>;
+        fn describe_typedef_target(ordinal: u32) -> Vec<String>;
+        fn get_enum_member_count(ordinal: u32) -> i32;
+        fn get_enum_member_name(ordinal: u32, index: u32) -> String;
+        fn get_enum_member_value(ordinal: u32, index: u32) -> i64;
+        fn is_bitmask_enum(ordinal: u32) -> bool;
+        fn print_type_expr(ordinal: u32) -> String;
+        fn parse_type_expr(expr: &str) -> u32;
     }
 }
\ No newline at end of file
@@ -1,6 +1,8 @@
+use std::collections::HashSet;
+
 use idalib::idb::IDB;
-use idalib::types::{builders, ArrayBuilder, EnumBuilder, PointerBuilder, TypeBuilder};
-use idalib::IDAError;
+use idalib::types::{builders, ArrayBuilder, EnumBuilder, PointerBuilder, Type, TypeBuilder};
+use idalib::{IDAError, enum_type};
 
 fn main() -> Result<(), IDAError> {
     // Open a binary for analysis
@@ -30,14 +32,55 @@ fn main() -> Result<(), IDAError> {
         .build()?;
     println!("Created enum 'Color' with ordinal {}", color_enum.ordinal());
 
+    // The same kind of enum, declared with the macro
+    let shade_enum = enum_type!(Shade: 1 { LIGHT = 1, MEDIUM, DARK }).build()?;
+    for (name, value) in shade_enum.enum_members()? {
+        println!("  Shade::{} = {}", name, value);
+    }
+
+    // Bitmask members without a value take successive bits
+    let access_enum = enum_type!(bitmask Access: 4 { READ, WRITE, EXEC }).build()?;
+    for (name, value) in access_enum.enum_members()? {
+        println!("  Access::{} = {:#x}", name, value);
+    }
+
     // Create an array of integers
     let int_array = builders::array_type(builders::int32(), 10).build()?;
-    println!("Created int[10] array with ordinal {}", int_array.ordinal());
+    println!(
+        "Created int[10] array with ordinal {} ({})",
+        int_array.ordinal(),
+        int_array.name_or_default()
+    );
+
+    // Zero-length arrays are rejected unless marked flexible
+    if let Err(e) = builders::array_type(builders::int32(), 0).build() {
+        println!("Rejected int[0]: {e}");
+    }
+    let open_array = builders::array_type(builders::int32(), 0)
+        .flexible(true)
+        .build()?;
+    println!("Created int[] array with ordinal {}", open_array.ordinal());
+
+    // wchar_t is 2 bytes for Windows compilers and 4 bytes elsewhere
+    let wide_array = builders::wchar().array(16).build()?;
+    println!(
+        "Created wchar_t[16] array of {:?} bytes ({:?}-byte wchar_t)",
+        wide_array.size(),
+        builders::wchar().to_type()?.size()
+    );
+
+    // Half-precision floats are 2 bytes on every processor
+    let half_array = builders::float16().array(8).build()?;
+    println!("Created _Float16[8] array of {:?} bytes", half_array.size());
 
     // Create a pointer to int
     let int_ptr = builders::pointer_type(builders::int32()).build()?;
     println!("Created int* pointer with ordinal {}", int_ptr.ordinal());
 
+    // Arrays and pointers can also be chained fluently
+    let int_array_ptr = builders::int32().array(10).build()?.ptr().build()?;
+    println!("Created int[10]* pointer with ordinal {}", int_array_ptr.ordinal());
+
     // Create a struct with enum and array fields
     let complex_struct = builders::struct_type("ComplexData")
         .field("status", status_enum.clone())
@@ -64,6 +107,30 @@ fn main() -> Result<(), IDAError> {
         struct_ptr.ordinal()
     );
 
+    // Refine the `int*` member of ComplexData now that its real type exists,
+    // and give its array a better name
+    complex_struct.set_field_type("next", &struct_ptr)?;
+    complex_struct.rename_field("values", "samples")?;
+
+    // Add a member discovered later, growing the struct
+    let old_size = complex_struct.size().unwrap_or(0);
+    complex_struct.append_field("checksum", &builders::uint32().to_type()?)?;
+    println!(
+        "ComplexData grew from {} to {} bytes",
+        old_size,
+        complex_struct.size().unwrap_or(0)
+    );
+    // Member types declared in place have no ordinal to show
+    for field in complex_struct.fields()? {
+        let ty = field
+            .ty
+            .map_or_else(|| "(no ordinal)".to_owned(), |ty| ty.to_string());
+        println!(
+            "  ComplexData.{} at +{} ({} bytes): {}",
+            field.name, field.offset, field.size, ty
+        );
+    }
+
     // Create a nested struct that uses pointers and arrays
     let linked_node = builders::struct_type("LinkedNode")
         .field("data", int_array)
@@ -75,7 +142,19 @@ fn main() -> Result<(), IDAError> {
         linked_node.ordinal()
     );
 
-    // Database is automatically saved and closed when idb goes out of scope
+    // Types compare by ordinal, so they can key sets and maps
+    let seen: HashSet<Type> = [&linked_node, &complex_struct, &linked_node]
+        .into_iter()
+        .cloned()
+        .collect();
+    println!("{} distinct struct types", seen.len());
+
+    // Keep the new types in a copy of the database, and leave the original
+    // unchanged when idb goes out of scope
+    let copy_path = format!("{binary_path}.types.i64");
+    idb.save_as(&copy_path)?;
+    idb.set_save_on_drop(false);
+    println!("Saved a copy of the database to {copy_path}");
     
     println!("\n✨ Successfully created all advanced types!");
     println!("\nYou can now use these types to annotate your binary in IDA Pro.");
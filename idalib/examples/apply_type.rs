@@ -0,0 +1,62 @@
+use std::env;
+use std::path::Path;
+
+use idalib::idb::IDB;
+use idalib::types::{TypeBuilder, builders};
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    // Setup: get binary path (and optionally a target address) from args
+    let args: Vec<String> = env::args().collect();
+    if args.len() < 2 {
+        eprintln!("Usage: {} <binary> [address]", args[0]);
+        std::process::exit(1);
+    }
+
+    let binary_path = Path::new(&args[1]);
+    if !binary_path.exists() {
+        eprintln!("Error: File '{}' not found", binary_path.display());
+        std::process::exit(1);
+    }
+
+    // Open the IDA database
+    let mut idb = IDB::open(binary_path)?;
+    println!("✨ Opened database for: {}", binary_path.display());
+
+    // Build a struct to stamp onto the data
+    let header = builders::struct_type("ExampleHeader")
+        .field("magic", builders::uint32())
+        .field("version", builders::uint16())
+        .field("flags", builders::uint16())
+        .field("length", builders::uint64())
+        .build()?;
+    println!("  ✓ Created struct 'ExampleHeader' (ordinal: {})", header.ordinal());
+
+    // Use the given address, or the start of the last segment
+    let address = match args.get(2) {
+        Some(arg) => u64::from_str_radix(arg.trim_start_matches("0x"), 16)?,
+        None => match idb.segments().last() {
+            Some((_, segment)) => segment.start_address(),
+            None => {
+                eprintln!("Error: database has no segments");
+                std::process::exit(1);
+            }
+        },
+    };
+
+    // Apply the struct and read the type back
+    match idb.apply_type(address, &header) {
+        Ok(()) => {
+            println!("  ✓ Applied 'ExampleHeader' at {:#x}", address);
+            if let Some(applied) = idb.get_type_at_address(address) {
+                println!("    Type at address: {:?}", applied.name());
+            }
+        }
+        Err(e) => println!("  ✗ Could not apply 'ExampleHeader' at {:#x}: {}", address, e),
+    }
+
+    // Database is automatically closed when idb goes out of scope
+    drop(idb);
+    println!("\n✅ Database closed successfully");
+
+    Ok(())
+}
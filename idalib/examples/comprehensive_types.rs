@@ -19,19 +19,43 @@ fn main() -> Result<(), IDAError> {
     
     // Create a flags struct with bitfields
     let flags_struct = builders::struct_type("FileFlags")
-        .field("reserved", builders::uint32())  // Regular field
-        .unsigned_bitfield("read_only", 0, 1)   // Bit 0
-        .unsigned_bitfield("hidden", 1, 1)      // Bit 1
-        .unsigned_bitfield("system", 2, 1)      // Bit 2
-        .unsigned_bitfield("archive", 3, 1)     // Bit 3
-        .unsigned_bitfield("directory", 4, 1)   // Bit 4
-        .unsigned_bitfield("encrypted", 5, 1)   // Bit 5
-        .signed_bitfield("priority", 6, 3)      // Bits 6-8 (3 bits for priority)
-        .unsigned_bitfield("version", 9, 4)     // Bits 9-12 (4 bits for version)
+        .field("reserved", builders::uint32())  // Regular field (bytes 0-3)
+        .unsigned_bitfield("read_only", 32, 1)  // Bit 32, past the field
+        .unsigned_bitfield("hidden", 33, 1)     // Bit 33
+        .unsigned_bitfield("system", 34, 1)     // Bit 34
+        .unsigned_bitfield("archive", 35, 1)    // Bit 35
+        .unsigned_bitfield("directory", 36, 1)  // Bit 36
+        .unsigned_bitfield("encrypted", 37, 1)  // Bit 37
+        .signed_bitfield("priority", 38, 3)     // Bits 38-40 (3 bits for priority)
+        .unsigned_bitfield("version", 41, 4)    // Bits 41-44 (4 bits for version)
         .build()?;
     
     println!("  Created FileFlags struct with bitfields (ordinal {})", flags_struct.ordinal());
 
+    // Bitfields added with auto_bitfield follow the members before them, and
+    // fields added after bitfields start past their storage
+    let packed_struct = builders::struct_type("PackedHeader")
+        .field("magic", builders::uint32())
+        .auto_bitfield("kind", 3, true)
+        .auto_bitfield("compressed", 1, true)
+        .field("length", builders::uint32())
+        .build()?;
+    for field in packed_struct.fields()? {
+        match field.bit_offset {
+            Some(bit) => println!("  PackedHeader.{} at bit {}", field.name, bit),
+            None => println!("  PackedHeader.{} at +{}", field.name, field.offset),
+        }
+    }
+
+    // Members may not share bytes with bitfields
+    let overlapping = builders::struct_type("Overlapping")
+        .field("value", builders::uint32())
+        .unsigned_bitfield("flag", 0, 1)
+        .build();
+    if let Err(e) = overlapping {
+        println!("  Overlapping bitfield rejected: {}", e);
+    }
+
     // =============================================
     // Complex Enums
     // =============================================
@@ -207,7 +231,8 @@ fn main() -> Result<(), IDAError> {
     
     println!("  Type validation is working correctly");
 
-    // Database is automatically saved and closed when idb goes out of scope
+    // The database is closed when idb goes out of scope, discarding the new
+    // types unless saving was enabled with `idb.set_save_on_drop(true)`
     println!("\n✅ All type features demonstrated successfully!");
     println!("\n📊 Summary of created types:");
     println!("  • Structs with bitfields for compact data representation");
@@ -60,7 +60,35 @@ fn main() -> Result<(), IDAError> {
         .calling_convention(CallingConvention::Stdcall)
         .build()?;
     println!("  Created stdcall WndProc (ordinal {})", stdcall_func.ordinal());
-    
+
+    // The purge has to match the stack arguments (on a 32-bit database)
+    for purged in [8, 12] {
+        let result = builders::function_type()
+            .returns(builders::int32())
+            .param("a", builders::uint32())
+            .param("b", builders::uint32())
+            .calling_convention(CallingConvention::Stdcall)
+            .purged_bytes(purged)
+            .build();
+        match result {
+            Ok(func) => println!(
+                "  Created stdcall purging {} bytes (ordinal {})",
+                purged,
+                func.ordinal()
+            ),
+            Err(e) => println!("  Purging {} bytes rejected: {}", purged, e),
+        }
+    }
+
+    // The caller cleans the stack for cdecl, so IDA ignores a purge there
+    let cdecl_purged = builders::function_type()
+        .returns(builders::int32())
+        .param("a", builders::uint32())
+        .calling_convention(CallingConvention::Cdecl)
+        .purged_bytes(4)
+        .build()?;
+    println!("  Created cdecl function ignoring its purge (ordinal {})", cdecl_purged.ordinal());
+
     // Fastcall function
     let fastcall_func = builders::function_type()
         .returns(builders::uint64())
@@ -152,6 +180,15 @@ fn main() -> Result<(), IDAError> {
         .calling_convention(CallingConvention::Thiscall)
         .build()?;
     println!("  Created const getter function (ordinal {})", const_getter.ordinal());
+
+    // Fastcall member function: `this` takes the first register argument
+    let fastcall_method = builders::function_type()
+        .returns(builders::int32())
+        .hidden_param("this", builders::pointer_type(my_class.clone()).build()?)
+        .param("index", builders::int32())
+        .calling_convention(CallingConvention::Fastcall)
+        .build()?;
+    println!("  Created fastcall member function (ordinal {})", fastcall_method.ordinal());
     
     // Constructor
     let constructor = builders::function_type()
@@ -272,7 +309,8 @@ fn main() -> Result<(), IDAError> {
         .build()?;
     println!("  Created syscall function (ordinal {})", syscall_func.ordinal());
 
-    // Database is automatically saved and closed when idb goes out of scope
+    // The database is closed when idb goes out of scope, discarding the new
+    // types unless saving was enabled with `idb.set_save_on_drop(true)`
     println!("\n✅ All function type features demonstrated successfully!");
     println!("\n📋 Function Types Created:");
     println!("  • Simple functions with parameters and return values");
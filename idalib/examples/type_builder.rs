@@ -2,7 +2,11 @@ use std::env;
 use std::path::Path;
 
 use idalib::idb::IDB;
-use idalib::types::{PrimitiveType, StructBuilder, TypeBuilder, builders};
+use idalib::meta::Compiler;
+use idalib::struct_type;
+use idalib::types::{
+    CallingConvention, FieldType, PrimitiveType, StructBuilder, TypeBuilder, builders,
+};
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
     // Setup: get binary path from args
@@ -22,6 +26,12 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     let mut idb = IDB::open(binary_path)?;
     println!("✨ Opened database for: {}", binary_path.display());
 
+    let compiler = idb.compiler_info();
+    println!(
+        "  Compiler {:?}, {:?} calls, {}-byte pointers",
+        compiler.id, compiler.cc, compiler.pointer_size
+    );
+
     // Test primitive type creation
     println!("\n📊 Testing Primitive Types");
     
@@ -46,6 +56,67 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         println!("    Name verified: {}", name);
     }
 
+    // Building it again would fail on the name; replacing redefines it in place
+    let point_struct = builders::struct_type("Point")
+        .field("x", builders::int32())
+        .field("y", builders::int32())
+        .replace_existing(true)
+        .build()?;
+    let point_count = idb
+        .iter_types()
+        .filter(|typ| typ.name().as_deref() == Some("Point"))
+        .count();
+    println!(
+        "  ✓ Rebuilt 'Point' (ordinal: {}, {} type(s) named Point)",
+        point_struct.ordinal(),
+        point_count
+    );
+
+    // Merging keeps x and y and adds z after them
+    let point_struct = builders::struct_type("Point")
+        .field("x", builders::int32())
+        .field("z", builders::int32())
+        .merge_into_existing(true)
+        .build()?;
+    println!(
+        "  ✓ Merged 'Point' (ordinal: {}, {} bytes):",
+        point_struct.ordinal(),
+        point_struct.size().unwrap_or(0)
+    );
+    for field in point_struct.fields()? {
+        println!("    +{:#x} {}", field.offset, field.name);
+    }
+
+    // Redefining an existing member with another type is a conflict
+    let conflict = builders::struct_type("Point")
+        .field("y", builders::double())
+        .merge_into_existing(true)
+        .build();
+    if let Err(e) = conflict {
+        println!("  ✓ Conflicting merge rejected: {}", e);
+    }
+
+    // The same kind of struct, declared with the macro
+    let pair_macro = struct_type!(PointPair {
+        first: { point_struct.clone() },
+        second: { point_struct.clone() },
+        next: ptr(Self),
+    })
+    .build()?;
+    let pair_chain = builders::struct_type("PointPairChain")
+        .field("first", point_struct.clone())
+        .field("second", point_struct.clone())
+        .self_ref("next")
+        .build()?;
+    let names = |typ: &idalib::types::Type| -> Result<Vec<String>, idalib::IDAError> {
+        Ok(typ.fields()?.into_iter().map(|field| field.name).collect())
+    };
+    println!(
+        "  ✓ Macro and builder agree: {}",
+        names(&pair_macro)? == names(&pair_chain)?
+            && pair_macro.size() == pair_chain.size()
+    );
+
     // Create a more complex struct
     println!("\n🔧 Creating Complex Struct");
     let data_struct = builders::struct_type("DataBlock")
@@ -57,6 +128,16 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     
     println!("  ✓ Created struct 'DataBlock' (ordinal: {})", data_struct.ordinal());
 
+    // The compiler decides how members are aligned
+    idb.set_compiler(Compiler::MS, CallingConvention::Cdecl)?;
+    let align_msvc = data_struct.alignment();
+    idb.set_compiler(Compiler::GNU, CallingConvention::Cdecl)?;
+    println!(
+        "    Alignment with MSVC: {:?}, with GCC: {:?}",
+        align_msvc,
+        data_struct.alignment()
+    );
+
     // Create a union
     println!("\n🔀 Creating Union");
     let variant_union = builders::union_type("Variant")
@@ -67,6 +148,21 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     
     println!("  ✓ Created union 'Variant' (ordinal: {})", variant_union.ordinal());
 
+    // Refer to types built earlier by name
+    println!("\n🔗 Creating Struct From Named Types");
+    let segment_struct = builders::struct_type("Segment")
+        .field("start", FieldType::from_name(&idb, "Point")?)
+        .field("end", FieldType::from_name(&idb, "Point")?)
+        .field("data", FieldType::from_name(&idb, "DataBlock*")?)
+        .field("next", FieldType::Pointer(Box::new(FieldType::forward("Segment"))))
+        .build()?;
+
+    println!("  ✓ Created struct 'Segment' (ordinal: {})", segment_struct.ordinal());
+
+    // Checkpoint the types built so far
+    idb.save()?;
+    println!("\n💾 Saved database with the new types");
+
     // List all types
     println!("\n📋 Types in Database");
     let types = idb.types();
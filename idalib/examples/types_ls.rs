@@ -1,4 +1,5 @@
 use idalib::idb::IDB;
+use idalib::types::{TypeBuilder, builders};
 
 fn main() -> anyhow::Result<()> {
     println!("Trying to open IDA database...");
@@ -105,5 +106,18 @@ fn main() -> anyhow::Result<()> {
     }
 
     println!("\nType assignment API test completed!");
+
+    // A database opened read-only rejects type changes
+    drop(idb);
+    let idb = IDB::open_readonly("./tests/ls")?;
+    println!("\nReopened read-only: {}", idb.is_read_only());
+    match builders::struct_type("ReadOnlyProbe")
+        .field("x", builders::int32())
+        .build()
+    {
+        Ok(_) => println!("Unexpectedly built a type in a read-only database"),
+        Err(e) => println!("Building a type failed as expected: {}", e),
+    }
+
     Ok(())
 }
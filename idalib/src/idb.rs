@@ -2,6 +2,7 @@ use std::ffi::CString;
 use std::marker::PhantomData;
 use std::mem::MaybeUninit;
 use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, Weak};
 
 use autocxx::c_int;
 
@@ -13,7 +14,8 @@ use crate::ffi::entry::{get_entry, get_entry_ordinal, get_entry_qty};
 use crate::ffi::func::{get_func, get_fchunk, get_func_qty, getn_func};
 use crate::ffi::hexrays::{decompile_func, init_hexrays_plugin, term_hexrays_plugin};
 use crate::ffi::ida::{
-    auto_wait, close_database_with, make_signatures, open_database_quiet, set_screen_ea,
+    auto_wait, close_database_with, make_signatures, open_database_quiet, save_database_as,
+    set_screen_ea,
 };
 use crate::ffi::insn::decode;
 use crate::ffi::loader::find_plugin;
@@ -23,7 +25,11 @@ use crate::ffi::search::{idalib_find_defined, idalib_find_imm, idalib_find_text}
 use crate::ffi::segment::{get_segm_by_name, get_segm_qty, getnseg, getseg};
 use crate::ffi::types::{
     idalib_parse_header_file,
+    idalib_get_type_ordinal_limit,
     idalib_get_type_ordinal_at_address,
+    get_type_ordinal_by_name, find_type_referrer, delete_type, type_name_exists, rename_type,
+    apply_type_to_address, is_function_type, apply_function_type, parse_type_decl,
+    parse_header_types, demangle_symbol, parse_type_expr, get_pointer_size, set_database_compiler,
 };
 use crate::ffi::util::{is_align_insn, next_head, prev_head, str2reg};
 use crate::ffi::xref::{xrefblk_t, xrefblk_t_first_from, xrefblk_t_first_to};
@@ -32,19 +38,34 @@ use crate::bookmarks::Bookmarks;
 use crate::decompiler::CFunction;
 use crate::func::{Function, FunctionId, NameFlags};
 use crate::insn::{Insn, Register};
-use crate::meta::{Metadata, MetadataMut};
+use crate::meta::{Compiler, CompilerInfo, Metadata, MetadataMut};
 use crate::name::NameList;
 use crate::plugin::Plugin;
 use crate::processor::Processor;
 use crate::segment::{Segment, SegmentId};
 use crate::strings::StringList;
-use crate::types::{Type, TypeList};
+use crate::types::builder::ffi_error;
+use crate::types::{CallingConvention, Type, TypeList};
 use crate::xref::{XRef, XRefQuery};
 use crate::{Address, AddressFlags, IDAError, IDARuntimeHandle, prepare_library};
 
+/// Read-only marker of the open database; the `IDB` handle owns the marker,
+/// so it goes away with the handle instead of carrying over to the next one
+static READ_ONLY: Mutex<Weak<()>> = Mutex::new(Weak::new());
+
+/// Fail if the open database is read-only; checked before changing types
+pub(crate) fn ensure_writable() -> Result<(), IDAError> {
+    let read_only = READ_ONLY.lock().unwrap();
+    if read_only.strong_count() > 0 {
+        return Err(IDAError::unsupported("The database is opened read-only"));
+    }
+    Ok(())
+}
+
 pub struct IDB {
     path: PathBuf,
     save: bool,
+    read_only: Option<Arc<()>>,
     decompiler: bool,
     _guard: IDARuntimeHandle,
     _marker: PhantomData<*const ()>,
@@ -157,18 +178,72 @@ impl IDB {
         Ok(Self {
             path: path.to_owned(),
             save,
+            read_only: None,
             decompiler,
             _guard,
             _marker: PhantomData,
         })
     }
 
+    /// Open a database for inspection only
+    ///
+    /// Nothing is saved when the database is closed, even if
+    /// `save_on_close(true)` is called, and creating, editing or applying
+    /// types fails with an `Unsupported` error. IDA still unpacks the
+    /// database to work on it, but never writes it back.
+    ///
+    /// Auto-analysis is not run, as it would change the database, so open a
+    /// database that was already analysed rather than a bare binary.
+    pub fn open_readonly(path: impl AsRef<Path>) -> Result<Self, IDAError> {
+        let mut idb = Self::open_with(path, false, false)?;
+        let marker = Arc::new(());
+        *READ_ONLY.lock().unwrap() = Arc::downgrade(&marker);
+        idb.read_only = Some(marker);
+        Ok(idb)
+    }
+
+    /// Check whether the database was opened with `open_readonly`
+    pub fn is_read_only(&self) -> bool {
+        self.read_only.is_some()
+    }
+
     pub fn path(&self) -> &Path {
         &self.path
     }
 
+    /// Same as `set_save_on_drop`
     pub fn save_on_close(&mut self, status: bool) {
-        self.save = status;
+        self.set_save_on_drop(status);
+    }
+
+    /// Choose whether the database is saved when this handle is dropped
+    ///
+    /// By default it is not: `open` discards all changes on drop, unless
+    /// saving was requested through `open_with` or `IDBOpenOptions::save`.
+    /// Databases opened with `open_readonly` are never saved.
+    pub fn set_save_on_drop(&mut self, save: bool) {
+        self.save = save;
+    }
+
+    /// Check whether the database will be saved when this handle is dropped
+    pub fn saves_on_drop(&self) -> bool {
+        self.save && !self.is_read_only()
+    }
+
+    /// Save the database, including its local types, to its own file now
+    ///
+    /// Unlike saving on drop, this reports failures, and lets long-running
+    /// scripts keep their work so far if they are interrupted later.
+    pub fn save(&mut self) -> Result<(), IDAError> {
+        ensure_writable()?;
+        save_database_as(None)
+    }
+
+    /// Save the database to `path` now, leaving the database file it was
+    /// opened from unchanged
+    pub fn save_as(&mut self, path: impl AsRef<Path>) -> Result<(), IDAError> {
+        ensure_writable()?;
+        save_database_as(Some(path.as_ref()))
     }
 
     pub fn auto_wait(&mut self) -> bool {
@@ -187,6 +262,47 @@ impl IDB {
         self.decompiler
     }
 
+    /// Get the compiler and ABI settings that types are laid out for
+    pub fn compiler_info(&self) -> CompilerInfo {
+        let meta = self.meta();
+        CompilerInfo {
+            id: meta.cc_id(),
+            cc: CallingConvention::from_ida_cc(meta.cc_cm().into()),
+            pointer_size: get_pointer_size(),
+            default_alignment: meta.cc_defalign().into(),
+        }
+    }
+
+    /// Set the compiler and default calling convention of the database
+    ///
+    /// The compiler decides how struct members and bitfields are laid out and
+    /// the conventions of functions, so types built afterwards are laid out
+    /// for it; set it before importing types written for a particular
+    /// compiler. The default alignment is reset to unspecified, so members
+    /// get their natural alignment. The sizes of `int`, `long` and the other
+    /// basic types are kept, even where the new compiler's ABI differs (such
+    /// as the 4-byte `long` of 64-bit MSVC).
+    pub fn set_compiler(
+        &mut self,
+        compiler: Compiler,
+        cc: CallingConvention,
+    ) -> Result<(), IDAError> {
+        ensure_writable()?;
+        if cc.is_special() {
+            return Err(IDAError::validation(format!(
+                "{:?} cannot be the default calling convention",
+                cc
+            )));
+        }
+        if !set_database_compiler(compiler as u32, cc.to_ida_cc()) {
+            return Err(ffi_error(format!(
+                "Failed to set the compiler to {:?}",
+                compiler
+            )));
+        }
+        Ok(())
+    }
+
     pub fn meta(&self) -> Metadata {
         Metadata::new()
     }
@@ -481,6 +597,55 @@ impl IDB {
         function.set_name(name)
     }
 
+    /// Demangle a C++ symbol name (MSVC, Itanium, ...) to its full form, like
+    /// `int __cdecl foo(int, char *)`
+    ///
+    /// Returns `None` if `mangled` is not a mangled name.
+    pub fn demangle(&self, mangled: &str) -> Option<String> {
+        self.demangle_with(mangled, false)
+    }
+
+    /// Demangle a C++ symbol name to the database's short form (usually just
+    /// the qualified name and parameters) if `short_form` is set, or else to
+    /// its full form
+    pub fn demangle_with(&self, mangled: &str, short_form: bool) -> Option<String> {
+        let demangled = demangle_symbol(mangled, short_form);
+        if demangled.is_empty() {
+            None
+        } else {
+            Some(demangled)
+        }
+    }
+
+    /// Get the function type of a mangled C++ symbol from its demangled
+    /// prototype
+    ///
+    /// The name is dropped from the prototype before it is parsed, so
+    /// qualified and template names are fine, but parameter types the C
+    /// parser cannot digest, such as template instances, are reported as an
+    /// error. Member functions don't get their implicit `this` parameter, and
+    /// manglings that don't encode a return type (Itanium manglings of
+    /// non-template functions) are given an `int` return type.
+    pub fn function_type_from_mangled(&mut self, mangled: &str) -> Result<Type, IDAError> {
+        ensure_writable()?;
+        let signature = self.demangle(mangled).ok_or_else(|| {
+            IDAError::validation(format!("'{}' is not a mangled name", mangled))
+        })?;
+        let prototype = unnamed_prototype(&signature).ok_or_else(|| {
+            IDAError::unsupported(format!(
+                "'{}' demangles to `{}`, which is not a function",
+                mangled, signature
+            ))
+        })?;
+        match parse_type_expr(&prototype) {
+            ordinal if ordinal != 0 && is_function_type(ordinal) => Ok(Type::from_ordinal(ordinal)),
+            _ => Err(IDAError::unsupported(format!(
+                "Cannot parse the prototype `{}` of '{}' (demangled to `{}`)",
+                prototype, mangled, signature
+            ))),
+        }
+    }
+
     pub fn bookmarks(&self) -> Bookmarks {
         Bookmarks::new(self)
     }
@@ -545,7 +710,18 @@ impl IDB {
         TypeList::new(self)
     }
 
+    /// Lazily iterate over every type in the local type library, skipping
+    /// deleted ordinals
+    pub fn iter_types(&self) -> impl Iterator<Item = Type> + '_ {
+        let limit = match unsafe { idalib_get_type_ordinal_limit() } {
+            u32::MAX => 0,
+            limit => limit,
+        };
+        (1..limit).filter_map(move |ordinal| self.get_type_by_ordinal(ordinal))
+    }
+
     pub fn parse_types_from_header<P: AsRef<Path>>(&self, header_path: P) -> Result<i32, IDAError> {
+        ensure_writable()?;
         let path_str = header_path.as_ref().to_string_lossy();
         let c_path = CString::new(path_str.as_ref()).map_err(IDAError::ffi)?;
 
@@ -558,6 +734,66 @@ impl IDB {
         }
     }
 
+    /// Parse a single C declaration and add the type it declares
+    ///
+    /// Accepts a struct, union, enum, typedef or function declaration such as
+    /// `struct Point { int x; int y; };`. A type with the same name is
+    /// replaced.
+    pub fn parse_decl(&mut self, decl: &str) -> Result<Type, IDAError> {
+        ensure_writable()?;
+        match parse_type_decl(decl) {
+            0 => Err(IDAError::validation(format!(
+                "Failed to parse declaration `{}`",
+                decl.trim()
+            ))),
+            ordinal => Ok(Type::from_ordinal(ordinal)),
+        }
+    }
+
+    /// Parse a preprocessed C header and add every type it declares
+    ///
+    /// The source must not contain `#include` or other preprocessor
+    /// directives. The new named types are returned in declaration order, so
+    /// each type comes after the types it depends on.
+    pub fn parse_header(&mut self, source: &str) -> Result<Vec<Type>, IDAError> {
+        ensure_writable()?;
+        let mut error_count = 0;
+        let ordinals = parse_header_types(source, &mut error_count);
+        match error_count {
+            0 => Ok(ordinals.into_iter().map(Type::from_ordinal).collect()),
+            n if n < 0 => Err(IDAError::ffi_with("Failed to parse header")),
+            n => Err(IDAError::ffi_with(format!("Failed to parse header: {n} error(s)"))),
+        }
+    }
+
+    /// Build the types described by a JSON document
+    ///
+    /// The document is an array of [`TypeDefinition`](crate::types::TypeDefinition)
+    /// entries, which refer to each other by name. On error, the message
+    /// names the entry that failed, and none of the document's types are
+    /// left behind.
+    #[cfg(feature = "serde")]
+    pub fn import_types_json(&mut self, json: &str) -> Result<Vec<Type>, IDAError> {
+        ensure_writable()?;
+        crate::types::schema::import_json(json)
+    }
+
+    /// Describe the structs, unions, enums and typedefs of the database as a
+    /// JSON document that `import_types_json` accepts
+    ///
+    /// Types from IDA's built-in type libraries are skipped; use
+    /// `export_types_json_with` to include them.
+    #[cfg(feature = "serde")]
+    pub fn export_types_json(&self) -> Result<String, IDAError> {
+        self.export_types_json_with(false)
+    }
+
+    /// Like `export_types_json`, optionally including types imported from
+    /// IDA's built-in type libraries
+    #[cfg(feature = "serde")]
+    pub fn export_types_json_with(&self, include_library_types: bool) -> Result<String, IDAError> {
+        crate::types::schema::export_json(include_library_types)
+    }
 
     /// Get the type at an address, if any
     pub fn get_type_at_address(&self, address: Address) -> Option<Type> {
@@ -569,6 +805,139 @@ impl IDB {
         }
     }
 
+    /// Apply a type to the data at an address, so IDA displays its fields
+    ///
+    /// The whole range covered by the type must be mapped and must not
+    /// contain instructions; data items already in the range are replaced.
+    pub fn apply_type(&mut self, ea: Address, ty: &Type) -> Result<(), IDAError> {
+        ensure_writable()?;
+        match apply_type_to_address(ea, ty.ordinal()) {
+            0 => Ok(()),
+            1 => Err(IDAError::not_found_with(format!(
+                "Type #{} does not exist or has no size",
+                ty.ordinal()
+            ))),
+            2 => Err(IDAError::ffi_with(format!(
+                "Address range for type #{} at {:#x} is not fully mapped",
+                ty.ordinal(),
+                ea
+            ))),
+            3 => Err(IDAError::ffi_with(format!(
+                "Type #{} at {:#x} would overlap an instruction",
+                ty.ordinal(),
+                ea
+            ))),
+            _ => Err(IDAError::ffi_with(format!(
+                "Failed to apply type #{} at {:#x}",
+                ty.ordinal(),
+                ea
+            ))),
+        }
+    }
+
+    /// Apply a function prototype to the function containing an address
+    pub fn set_function_type(&mut self, ea: Address, func_ty: &Type) -> Result<(), IDAError> {
+        ensure_writable()?;
+        if !is_function_type(func_ty.ordinal()) {
+            return Err(IDAError::validation(format!(
+                "Type #{} is not a function type",
+                func_ty.ordinal()
+            )));
+        }
+
+        let func_ea = self
+            .function_at(ea)
+            .map(|f| f.start_address())
+            .ok_or_else(|| IDAError::not_found_with(format!("No function contains {:#x}", ea)))?;
+
+        if !apply_function_type(func_ea, func_ty.ordinal()) {
+            return Err(IDAError::ffi_with(format!(
+                "Failed to apply type #{} to the function at {:#x}",
+                func_ty.ordinal(),
+                func_ea
+            )));
+        }
+        Ok(())
+    }
+
+    /// Get a named type from the local type library
+    ///
+    /// Types only defined in a loaded base type library are imported into the
+    /// local one, so the returned ordinal can be used like any other.
+    pub fn get_type_by_name(&self, name: &str) -> Option<Type> {
+        match get_type_ordinal_by_name(name) {
+            0 => None,
+            ordinal => Some(Type::from_ordinal(ordinal)),
+        }
+    }
+
+    /// Get the type with the given ordinal, if it exists
+    ///
+    /// Unlike `Type::from_ordinal`, this checks that the ordinal resolves to
+    /// a type in the local type library.
+    pub fn get_type_by_ordinal(&self, ordinal: u32) -> Option<Type> {
+        self.types().get_by_index(ordinal)
+    }
+
+    /// Delete a type from the local type library
+    ///
+    /// Fails if a named type still refers to it, naming that type in the
+    /// error.
+    pub fn delete_type(&mut self, ordinal: u32) -> Result<(), IDAError> {
+        ensure_writable()?;
+        if self.get_type_by_ordinal(ordinal).is_none() {
+            return Err(IDAError::not_found_with(format!("Type #{} does not exist", ordinal)));
+        }
+
+        let referrer = find_type_referrer(ordinal);
+        if !referrer.is_empty() {
+            return Err(IDAError::ffi_with(format!(
+                "Type #{} is still referenced by '{}'",
+                ordinal, referrer
+            )));
+        }
+
+        if !delete_type(ordinal) {
+            return Err(IDAError::ffi_with(format!("Failed to delete type #{}", ordinal)));
+        }
+        Ok(())
+    }
+
+    /// Rename a type
+    ///
+    /// Other types refer to it by ordinal, so they pick up the new name.
+    pub fn rename_type(&mut self, ordinal: u32, new_name: &str) -> Result<(), IDAError> {
+        ensure_writable()?;
+        if new_name.is_empty() {
+            return Err(IDAError::validation("Type name cannot be empty"));
+        }
+        if type_name_exists(new_name) {
+            return Err(IDAError::already_exists(format!(
+                "A type named '{}' already exists",
+                new_name
+            )));
+        }
+        if self.get_type_by_ordinal(ordinal).is_none() {
+            return Err(IDAError::not_found_with(format!("Type #{} does not exist", ordinal)));
+        }
+
+        if !rename_type(ordinal, new_name) {
+            return Err(IDAError::ffi_with(format!(
+                "Failed to rename type #{} to '{}'",
+                ordinal, new_name
+            )));
+        }
+        Ok(())
+    }
+
+    /// Delete a named type from the local type library
+    pub fn delete_type_by_name(&mut self, name: &str) -> Result<(), IDAError> {
+        let typ = self
+            .get_type_by_name(name)
+            .ok_or_else(|| IDAError::not_found_with(format!("Type '{}' does not exist", name)))?;
+        self.delete_type(typ.ordinal())
+    }
+
 
     pub fn address_to_string(&self, ea: Address) -> Option<String> {
         let s = unsafe { idalib_ea2str(ea.into()) };
@@ -633,6 +1002,84 @@ impl IDB {
     }
 }
 
+/// Turn a demangled function signature into a type expression by dropping the
+/// (qualified) function name, access specifiers and method qualifiers:
+/// `public: int __thiscall Foo::bar(int) const` becomes `int __thiscall (int)`
+fn unnamed_prototype(signature: &str) -> Option<String> {
+    // The parameter list is the last parenthesised group
+    let close = signature.rfind(')')?;
+    let mut depth = 0;
+    let mut open = None;
+    for (index, c) in signature[..=close].char_indices().rev() {
+        match c {
+            ')' => depth += 1,
+            '(' => {
+                depth -= 1;
+                if depth == 0 {
+                    open = Some(index);
+                    break;
+                }
+            }
+            _ => {}
+        }
+    }
+    let open = open?;
+
+    // Operator names may contain brackets and spaces (`operator<`,
+    // `operator->`, `operator int`), so those names are only searched back
+    // from the `operator` keyword
+    let operator = operator_keyword(&signature[..open]);
+    let name_end = operator.unwrap_or(open);
+    // Conversion operators are named after the type they return
+    let conversion = operator
+        .map(|start| signature[start + "operator".len()..open].trim())
+        .filter(|name| name.starts_with(|c: char| c.is_alphabetic() || c == '_'))
+        .filter(|name| !matches!(*name, "new" | "new[]" | "delete" | "delete[]" | "co_await"));
+
+    // The name runs back to the first space outside template arguments
+    let mut depth = 0;
+    let mut name_start = 0;
+    for (index, c) in signature[..name_end].char_indices().rev() {
+        match c {
+            '>' => depth += 1,
+            '<' => depth -= 1,
+            ' ' if depth == 0 => {
+                name_start = index + 1;
+                break;
+            }
+            _ => {}
+        }
+    }
+
+    let prefix: Vec<&str> = signature[..name_start]
+        .split_whitespace()
+        .filter(|word| {
+            !matches!(
+                *word,
+                "public:" | "protected:" | "private:" | "[thunk]:" | "virtual" | "static"
+            )
+        })
+        .collect();
+    let return_type = match (conversion, prefix.is_empty()) {
+        (Some(conversion), true) => conversion.to_owned(),
+        (Some(conversion), false) => format!("{} {}", conversion, prefix.join(" ")),
+        (None, true) => "int".to_owned(),
+        (None, false) => prefix.join(" "),
+    };
+    Some(format!("{} {}", return_type, &signature[open..=close]))
+}
+
+/// Position of the last `operator` keyword in `text`, if any
+fn operator_keyword(text: &str) -> Option<usize> {
+    let is_ident = |c: char| c.is_alphanumeric() || c == '_';
+    text.rmatch_indices("operator")
+        .map(|(start, keyword)| (start, start + keyword.len()))
+        .find(|&(start, end)| {
+            !text[..start].ends_with(is_ident) && !text[end..].starts_with(is_ident)
+        })
+        .map(|(start, _)| start)
+}
+
 impl Drop for IDB {
     fn drop(&mut self) {
         if self.decompiler {
@@ -640,7 +1087,7 @@ impl Drop for IDB {
                 term_hexrays_plugin();
             }
         }
-        close_database_with(self.save);
+        close_database_with(self.saves_on_drop());
     }
 }
 
@@ -675,3 +1122,73 @@ impl<'a> Iterator for EntryPointIter<'a> {
         (0, Some(lim))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unnamed_prototypes() {
+        let cases = [
+            (
+                "int __cdecl main(int, char * *)",
+                "int __cdecl (int, char * *)",
+            ),
+            (
+                "public: int __thiscall Foo::bar(int) const",
+                "int __thiscall (int)",
+            ),
+            (
+                "public: virtual void __thiscall std::vector<int, std::allocator<int> >::clear(void)",
+                "void __thiscall (void)",
+            ),
+            ("__cdecl Foo::Foo(void)", "__cdecl (void)"),
+            ("foo(int)", "int (int)"),
+            (
+                "public: bool __thiscall Foo::operator<(class Foo const &) const",
+                "bool __thiscall (class Foo const &)",
+            ),
+            (
+                "public: bool __thiscall Foo::operator>=(class Foo const &) const",
+                "bool __thiscall (class Foo const &)",
+            ),
+            (
+                "public: class Foo & __thiscall Foo::operator<<(int)",
+                "class Foo & __thiscall (int)",
+            ),
+            (
+                "public: class Bar * __thiscall Foo::operator->(void)",
+                "class Bar * __thiscall (void)",
+            ),
+            (
+                "public: int __thiscall Foo::operator()(int, int)",
+                "int __thiscall (int, int)",
+            ),
+            (
+                "public: __thiscall Foo::operator int(void) const",
+                "int __thiscall (void)",
+            ),
+            (
+                "void * __cdecl operator new(unsigned int)",
+                "void * __cdecl (unsigned int)",
+            ),
+            (
+                "public: bool __thiscall Foo<int>::operator< <int>(int)",
+                "bool __thiscall (int)",
+            ),
+            (
+                "public: void __thiscall Foo::operator delete[](void *)",
+                "void __thiscall (void *)",
+            ),
+            ("int __cdecl get_operator(int)", "int __cdecl (int)"),
+        ];
+        for (signature, expected) in cases {
+            assert_eq!(
+                unnamed_prototype(signature).as_deref(),
+                Some(expected),
+                "{signature}"
+            );
+        }
+        assert_eq!(unnamed_prototype("no_parameters"), None);
+    }
+}
@@ -97,7 +97,7 @@ pub mod xref;
 
 pub use idalib_sys as ffi;
 
-pub use ffi::IDAError;
+pub use ffi::{IDAError, IDAErrorKind};
 pub use idb::{IDB, IDBOpenOptions};
 pub use license::{LicenseId, is_valid_license, license_id};
 
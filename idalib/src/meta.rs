@@ -8,6 +8,7 @@ use crate::ffi::BADADDR;
 use crate::ffi::inf::*;
 use crate::ffi::nalt::*;
 use crate::idb::IDB;
+use crate::types::CallingConvention;
 
 bitflags! {
     #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
@@ -120,6 +121,19 @@ pub enum Compiler {
     UNSURE = COMP_UNSURE as _,
 }
 
+/// The compiler and ABI settings of a database, which determine type sizes
+/// and layouts
+#[derive(Debug, Clone, Copy)]
+pub struct CompilerInfo {
+    pub id: Compiler,
+    /// Default calling convention of functions
+    pub cc: CallingConvention,
+    /// Size of a data pointer in bytes
+    pub pointer_size: u32,
+    /// Default alignment of struct members in bytes, or 0 if unspecified
+    pub default_alignment: u32,
+}
+
 pub struct Metadata<'a> {
     _marker: PhantomData<&'a IDB>,
 }
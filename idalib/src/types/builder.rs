@@ -1,114 +1,801 @@
 use crate::ffi::types::{
-    create_struct_type, create_union_type, add_field_to_type,
-    finalize_type, get_primitive_type_ordinal, get_type_size,
-    create_enum_type, add_enum_member,
-    create_array_type, create_pointer_type,
+    create_struct_type, create_union_type, forward_declare_struct, add_field_to_type,
+    add_baseclass,
+    finalize_type, get_primitive_type_ordinal, get_type_size, get_type_alignment,
+    set_type_alignment, set_udt_unaligned, set_field_comment, set_udt_kind, clear_udt_members,
+    reset_to_empty_udt, save_type_definition, restore_type_definition,
+    delete_type, set_udt_cpp_object, set_udt_vftable, set_udt_member_vftable,
+    create_enum_type, add_enum_member, set_enum_bitmask, set_enum_signed, set_enum_radix,
+    create_array_type, create_pointer_type, create_qualified_type, create_sized_pointer_type,
     add_bitfield_to_struct,
-    create_function_type, add_function_parameter,
-    set_function_attributes, create_function_pointer_type,
+    create_function_type, add_function_parameter, add_function_parameter_in_register,
+    set_function_attributes, set_function_purged, set_function_spoiled,
+    set_function_return_register,
+    create_function_pointer_type,
+    get_pointer_size, get_wchar_size, get_or_create_primitive_typedef, create_typedef, take_last_type_error,
+    idalib_is_valid_type_ordinal, get_type_ordinal_by_name, is_function_type, parse_type_expr,
+    compare_types, get_type_name, get_pointer_target, get_array_length, get_array_element_ordinal,
+    is_union_type, get_udt_member_count, get_udt_member_names, get_udt_member_offsets,
+    get_udt_member_sizes, get_udt_member_bitfields, get_udt_member_comments, get_udt_member_roles,
+    get_udt_layout, get_udt_member_bitfield_storage, describe_udt_member_type,
+    idalib_get_type_ordinal_limit,
 };
+use crate::idb::{IDB, ensure_writable};
 use crate::types::Type;
 use crate::IDAError;
 
+/// Error for a failed type library call, carrying IDA's reason for the
+/// failure when it gave one
+pub(crate) fn ffi_error(message: impl Into<String>) -> IDAError {
+    let reason = take_last_type_error();
+    IDAError::ffi_failure(message.into(), (!reason.is_empty()).then_some(reason))
+}
+
+/// First ordinal not yet in use; every type created from now on gets an
+/// ordinal at or above it
+fn ordinal_limit() -> u32 {
+    unsafe { idalib_get_type_ordinal_limit() }
+}
+
+/// Delete the types created since `ordinal_limit` returned `limit`
+fn delete_types_since(limit: u32) {
+    let end = ordinal_limit();
+    if limit == u32::MAX || end == u32::MAX {
+        return;
+    }
+    for ordinal in (limit.max(1)..end).rev() {
+        if unsafe { idalib_is_valid_type_ordinal(ordinal) } {
+            delete_type(ordinal);
+        }
+    }
+}
+
+/// Put back the definition a failed build replaced, as saved by
+/// `save_type_definition`, or else leave the type empty
+fn restore_replaced_type(ordinal: u32, original: &[u8]) {
+    if !restore_type_definition(ordinal, original) {
+        take_last_type_error();
+        clear_udt_members(ordinal);
+    }
+}
+
+/// Run `build`, deleting every type it created if it fails
+///
+/// Besides the type being built, this covers the helper types made along the
+/// way: pointers, arrays, qualified types, gap arrays, inline structs and
+/// vtables.
+fn deleting_created_on_error<T>(
+    build: impl FnOnce() -> Result<T, IDAError>,
+) -> Result<T, IDAError> {
+    let limit = ordinal_limit();
+    build().inspect_err(|_| delete_types_since(limit))
+}
+
 /// Trait for all type builders
-pub trait TypeBuilder: Sized {
+pub trait TypeBuilder {
     /// Build the type and save it to the type library
-    fn build(self) -> Result<Type, IDAError>;
+    fn build(self) -> Result<Type, IDAError>
+    where
+        Self: Sized;
+
+    /// Build the boxed builder, resolving forward references through
+    /// `registry`; used by `builders::build_all`
+    fn build_boxed(self: Box<Self>, registry: &mut TypeRegistry) -> Result<Type, IDAError>;
+
+    /// Name of the type this builder defines, through which other builders
+    /// can refer to it
+    fn defined_name(&self) -> Option<&str> {
+        None
+    }
+
+    /// Names of the types this builder embeds by value, which must be built
+    /// before it
+    fn embedded_refs(&self) -> Vec<&str> {
+        Vec::new()
+    }
     
     /// Validate the builder configuration before building
     fn validate(&self) -> Result<(), IDAError> {
         Ok(())
     }
+
+    /// Check that `build` would succeed as far as can be told without
+    /// creating anything: validate the configuration and make sure every type
+    /// it refers to can be resolved
+    fn check(&self) -> Result<(), IDAError> {
+        self.validate()
+    }
 }
 
 /// Trait for type validation
+///
+/// Every builder implements this, and `build` runs it first. Calling it
+/// directly checks a configuration without touching the database, e.g. to
+/// report a mistake in user input before attempting a build:
+///
+/// ```ignore
+/// let array = ArrayBuilder::new(builders::int32(), 0);
+/// if let Err(e) = TypeValidator::validate(&array) {
+///     println!("{}", e.message()); // Array cannot have zero elements ...
+/// }
+/// ```
+///
+/// Validation only looks at the configuration itself; use
+/// `TypeBuilder::check` to also make sure the types it refers to exist.
 pub trait TypeValidator {
-    /// Validate the type configuration
+    /// Validate the type configuration, returning a `Validation` error
+    /// describing the first problem found
     fn validate(&self) -> Result<(), IDAError>;
 }
 
 /// Builder for creating struct types
-#[derive(Debug)]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct StructBuilder {
     name: String,
     fields: Vec<StructField>,
+    #[cfg_attr(feature = "serde", serde(default))]
     bitfields: Vec<BitfieldInfo>,
+    #[cfg_attr(feature = "serde", serde(default))]
     is_union: bool,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pack: Option<u32>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    align: Option<u32>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    fill_gaps: bool,
+    #[cfg_attr(feature = "serde", serde(default))]
+    total_size: Option<u64>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    no_tail_padding: bool,
+    #[cfg_attr(feature = "serde", serde(default))]
+    is_class: bool,
+    #[cfg_attr(feature = "serde", serde(default))]
+    virtual_methods: Vec<VirtualMethod>,
+    /// Whether to redefine a type of the same name in place
+    #[cfg_attr(feature = "serde", serde(default))]
+    replace_existing: bool,
+    /// Whether to add the members to a type of the same name, keeping its
+    /// members
+    #[cfg_attr(feature = "serde", serde(default))]
+    merge_into_existing: bool,
+    /// Bit offset of the next `auto_bitfield`, while bitfields are being
+    /// added back to back
+    #[cfg_attr(feature = "serde", serde(skip))]
+    next_auto_bit: Option<u32>,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 struct StructField {
     name: String,
     field_type: FieldType,
+    #[cfg_attr(feature = "serde", serde(default))]
     offset: Option<u64>,
+    /// Comment text and whether it is repeatable
+    #[cfg_attr(feature = "serde", serde(default))]
+    comment: Option<(String, bool)>,
+    /// Whether this is a flexible array member of `field_type` elements
+    #[cfg_attr(feature = "serde", serde(default))]
+    is_flexible: bool,
+    /// Whether this is the `__vftable` member, which points at the vtable
+    /// built for the virtual methods
+    #[cfg_attr(feature = "serde", serde(default))]
+    is_vftable: bool,
+    /// Whether this is the base class member of a derived type
+    #[cfg_attr(feature = "serde", serde(default))]
+    is_baseclass: bool,
+}
+
+/// Entry of a class's vtable
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+struct VirtualMethod {
+    name: String,
+    #[cfg_attr(feature = "serde", serde(with = "type_by_name"))]
+    function_type: Type,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 struct BitfieldInfo {
     name: String,
     bit_offset: u32,
     bit_width: u32,
+    #[cfg_attr(feature = "serde", serde(default))]
     is_unsigned: bool,
+    /// Size in bytes of the declared storage type, like 1 for `uint8_t x : 3`;
+    /// derived from the bit range if not set
+    #[cfg_attr(feature = "serde", serde(default))]
+    storage_bytes: Option<u32>,
+    /// Number of fields added before the bitfield; fields added after it are
+    /// placed past its storage. `None` if unknown, as in hand-written JSON
+    #[cfg_attr(feature = "serde", serde(default))]
+    fields_before: Option<usize>,
+}
+
+impl BitfieldInfo {
+    /// Offset just past the bytes holding the bitfield, including the rest of
+    /// its storage unit
+    fn storage_end(&self) -> u64 {
+        let end = (self.bit_offset + self.bit_width).div_ceil(8) as u64;
+        match self.storage_bytes {
+            Some(storage) if storage > 0 => end.next_multiple_of(storage as u64),
+            _ => end,
+        }
+    }
+}
+
+/// A member of the type a `StructBuilder` merges into
+struct ExistingMember {
+    name: String,
+    /// Description of the member's type; `None` for bitfields
+    member_type: Option<Vec<TypeStep>>,
+    /// Offset and size of the member in bits
+    bits: (u64, u64),
+}
+
+/// Find the member that overlaps the bit range `(offset, size)`
+fn overlapped_member(members: &[ExistingMember], bits: (u64, u64)) -> Option<&ExistingMember> {
+    let (offset, size) = bits;
+    members.iter().find(|member| {
+        let (member_offset, member_size) = member.bits;
+        offset < member_offset + member_size && member_offset < offset + size
+    })
 }
 
 /// Represents a field type in a struct/union
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum FieldType {
     /// A primitive type (int, float, etc.)
     Primitive(PrimitiveType),
     /// Reference to an existing type
+    ///
+    /// Serialized by type name, since ordinals differ between databases, or
+    /// as a C type expression if the type has no name.
+    #[cfg_attr(feature = "serde", serde(with = "type_by_name"))]
     Existing(Type),
-    /// Forward reference to a type being built (for self-referential types)
-    /// The string is the name of the type being referenced
+    /// Forward reference by name to a type being built (see `TypeRegistry`)
+    ///
+    /// The referenced type is embedded by value; wrap the reference in
+    /// `Pointer` to point at it instead, which is required for
+    /// self-referential types.
     ForwardRef(String),
+    /// A struct/union defined in place, without a type name of its own; it is
+    /// built together with the type that uses it
+    Inline(Box<StructBuilder>),
+    /// Pointer to another field type, created together with the type that
+    /// uses it
+    Pointer(Box<FieldType>),
+    /// Array of another field type with the given number of elements,
+    /// created together with the type that uses it
+    Array(Box<FieldType>, u32),
+    /// A C type expression, like `int (*)(int)`, parsed when the type that
+    /// uses it is built; describes types that have no ordinal to refer to
+    Declared(String),
+}
+
+/// One step of the description of a type that may have no ordinal, from the
+/// outside in (see `describe_udt_member_type`)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum TypeStep {
+    Pointer,
+    Array(u32),
+    /// A type with an ordinal
+    Numbered(u32),
+    /// A basic type code and its C name
+    Primitive(u32, String),
+    /// A C type expression
+    Declared(String),
+}
+
+impl TypeStep {
+    /// Parse the steps of a description from the type bridge
+    pub(crate) fn parse_all(steps: Vec<String>) -> Vec<TypeStep> {
+        steps
+            .into_iter()
+            .map(|step| {
+                let (kind, value) = step.split_once(' ').unwrap_or((&step, ""));
+                let number = || value.split(' ').next().and_then(|n| n.parse().ok());
+                match kind {
+                    "pointer" => TypeStep::Pointer,
+                    "array" => TypeStep::Array(number().unwrap_or(0)),
+                    "ordinal" => TypeStep::Numbered(number().unwrap_or(0)),
+                    "primitive" => TypeStep::Primitive(
+                        number().unwrap_or(0),
+                        value
+                            .split_once(' ')
+                            .map_or("", |(_, name)| name)
+                            .to_owned(),
+                    ),
+                    _ => TypeStep::Declared(value.to_owned()),
+                }
+            })
+            .collect()
+    }
+}
+
+#[cfg(feature = "serde")]
+mod type_by_name {
+    use serde::de::Error as _;
+    use serde::ser::Error as _;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    use crate::ffi::types::{
+        get_type_name, get_type_ordinal_by_name, parse_type_expr, print_type_expr,
+    };
+    use crate::idb::ensure_writable;
+    use crate::types::Type;
+
+    /// Unnamed types, like `char[16]`, are written as C type expressions
+    pub fn serialize<S: Serializer>(typ: &Type, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut name = get_type_name(typ.ordinal());
+        if name.is_empty() {
+            name = print_type_expr(typ.ordinal());
+        }
+        if name.is_empty() {
+            return Err(S::Error::custom(format!(
+                "type #{} cannot be serialized",
+                typ.ordinal()
+            )));
+        }
+        serializer.serialize_str(&name)
+    }
+
+    /// Parsing a C type expression adds the type to the type library, so it
+    /// fails on a read-only database
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Type, D::Error> {
+        let name = String::deserialize(deserializer)?;
+        match get_type_ordinal_by_name(&name) {
+            0 => {
+                ensure_writable().map_err(D::Error::custom)?;
+                match parse_type_expr(&name) {
+                    0 => Err(D::Error::custom(format!("unknown type `{name}`"))),
+                    ordinal => Ok(Type::from_ordinal(ordinal)),
+                }
+            }
+            ordinal => Ok(Type::from_ordinal(ordinal)),
+        }
+    }
+}
+
+impl FieldType {
+    /// Size and alignment of the type, or `None` if it is opaque
+    fn layout(&self) -> Option<(u64, u64)> {
+        let ordinal = match self {
+            // Half floats are 2 bytes on every processor
+            FieldType::Primitive(PrimitiveType::Float16) => return Some((2, 2)),
+            FieldType::Primitive(prim) => prim.ordinal(),
+            FieldType::Existing(typ) => typ.ordinal(),
+            FieldType::ForwardRef(_) | FieldType::Declared(_) => return None,
+            FieldType::Inline(inner) => return inner.type_layout(),
+            FieldType::Pointer(_) => {
+                let size = get_pointer_size() as u64;
+                return Some((size, size));
+            }
+            FieldType::Array(element, num_elements) => {
+                let (size, align) = element.layout()?;
+                return Some((size * *num_elements as u64, align));
+            }
+        };
+        match get_type_size(ordinal) {
+            0 => None,
+            size => Some((size, get_type_alignment(ordinal).max(1))),
+        }
+    }
+
+    /// Replace forward references by the types registered for them
+    fn resolved(self, registry: &mut TypeRegistry) -> Result<FieldType, IDAError> {
+        match self {
+            FieldType::ForwardRef(name) => {
+                Ok(FieldType::Existing(registry.resolve_embedded(&name)?))
+            }
+            FieldType::Pointer(target) => Ok(FieldType::Pointer(Box::new(match *target {
+                FieldType::ForwardRef(name) => FieldType::Existing(registry.resolve(&name)?),
+                target => target.resolved(registry)?,
+            }))),
+            FieldType::Array(element, num_elements) => Ok(FieldType::Array(
+                Box::new(element.resolved(registry)?),
+                num_elements,
+            )),
+            field_type => Ok(field_type),
+        }
+    }
+
+    /// Name of the type embedded by value through a forward reference, if any
+    fn embedded_ref(&self) -> Option<&str> {
+        match self {
+            FieldType::ForwardRef(name) => Some(name),
+            FieldType::Array(element, _) => element.embedded_ref(),
+            _ => None,
+        }
+    }
+
+    /// Describe a type from its steps, turning numbered types into field
+    /// types with `numbered`; `None` if the description is incomplete or
+    /// `numbered` fails
+    pub(crate) fn from_steps(
+        steps: &[TypeStep],
+        numbered: &mut impl FnMut(u32) -> Option<FieldType>,
+    ) -> Option<FieldType> {
+        match steps {
+            [TypeStep::Pointer, target @ ..] => Some(FieldType::Pointer(Box::new(
+                FieldType::from_steps(target, numbered)?,
+            ))),
+            [TypeStep::Array(num_elements), element @ ..] => Some(FieldType::Array(
+                Box::new(FieldType::from_steps(element, numbered)?),
+                *num_elements,
+            )),
+            [TypeStep::Numbered(ordinal)] => numbered(*ordinal),
+            [TypeStep::Primitive(code, name)] => Some(
+                PrimitiveType::from_ida_type(*code)
+                    .map_or_else(|| FieldType::Declared(name.clone()), FieldType::Primitive),
+            ),
+            [TypeStep::Declared(expr)] => Some(FieldType::Declared(expr.clone())),
+            _ => None,
+        }
+    }
+
+    /// Whether this describes the type with these steps, matching forward
+    /// references by name and inline structs by member names
+    fn matches(&self, steps: &[TypeStep]) -> bool {
+        let other = match steps {
+            [TypeStep::Numbered(ordinal)] => *ordinal,
+            [TypeStep::Primitive(code, _)] => get_primitive_type_ordinal(*code),
+            [TypeStep::Declared(expr)] => {
+                return matches!(self, FieldType::Declared(own) if own == expr);
+            }
+            [TypeStep::Pointer, target @ ..] => {
+                return match self {
+                    FieldType::Pointer(own) => own.matches(target),
+                    FieldType::Existing(typ) => match get_pointer_target(typ.ordinal()) {
+                        0 => false,
+                        own => FieldType::Existing(Type::from_ordinal(own)).matches(target),
+                    },
+                    _ => false,
+                };
+            }
+            [TypeStep::Array(num_elements), element @ ..] => {
+                return match self {
+                    FieldType::Array(own, own_elements) => {
+                        own_elements == num_elements && own.matches(element)
+                    }
+                    FieldType::Existing(typ) => {
+                        get_array_length(typ.ordinal()) == *num_elements as i64
+                            && match get_array_element_ordinal(typ.ordinal()) {
+                                0 => false,
+                                own => {
+                                    FieldType::Existing(Type::from_ordinal(own)).matches(element)
+                                }
+                            }
+                    }
+                    _ => false,
+                };
+            }
+            _ => return false,
+        };
+        match self {
+            FieldType::Primitive(prim) => compare_types(prim.ordinal(), other),
+            FieldType::Existing(typ) => compare_types(typ.ordinal(), other),
+            FieldType::ForwardRef(name) => get_type_name(other) == *name,
+            FieldType::Inline(inner) => {
+                let names = inner.field_names();
+                get_udt_member_names(other) == names
+            }
+            FieldType::Pointer(_) | FieldType::Array(..) | FieldType::Declared(_) => false,
+        }
+    }
+
+    /// Get a `const` and/or `volatile` qualified version of this type
+    ///
+    /// The type is created right away, so forward references are rejected.
+    pub fn qualified(self, is_const: bool, is_volatile: bool) -> Result<FieldType, IDAError> {
+        let ordinal = self.into_ordinal("qualified types")?;
+        if ordinal == 0 {
+            return Err(ffi_error("Invalid type to qualify"));
+        }
+        if !is_const && !is_volatile {
+            return Ok(FieldType::Existing(Type::from_ordinal(ordinal)));
+        }
+
+        ensure_writable()?;
+        match create_qualified_type(ordinal, is_const, is_volatile) {
+            0 => Err(ffi_error("Failed to create qualified type")),
+            qualified => Ok(FieldType::Existing(Type::from_ordinal(qualified))),
+        }
+    }
+
+    /// Resolve the type to an ordinal where forward references cannot be
+    /// resolved; `context` describes the use for error messages
+    fn into_ordinal(self, context: &str) -> Result<u32, IDAError> {
+        let ordinal = match self {
+            FieldType::Primitive(prim) => prim.ordinal(),
+            FieldType::Existing(typ) => typ.ordinal(),
+            FieldType::ForwardRef(_) => {
+                return Err(IDAError::unsupported(format!(
+                    "Forward references not supported in {}",
+                    context
+                )));
+            }
+            FieldType::Inline(inner) => inner.build_inline()?.ordinal(),
+            FieldType::Pointer(target) => match target.into_ordinal(context)? {
+                0 => 0,
+                target_ordinal => create_pointer_type(target_ordinal),
+            },
+            FieldType::Array(element, num_elements) => match element.into_ordinal(context)? {
+                0 => 0,
+                element_ordinal => create_array_type(element_ordinal, num_elements),
+            },
+            FieldType::Declared(expr) => {
+                ensure_writable()?;
+                match parse_type_expr(&expr) {
+                    0 => {
+                        return Err(IDAError::validation(format!(
+                            "Invalid type `{expr}` in {context}"
+                        )));
+                    }
+                    ordinal => ordinal,
+                }
+            }
+        };
+        Ok(ordinal)
+    }
+
+    /// Check that `into_ordinal` would resolve the type, without creating
+    /// anything
+    fn check_resolvable(&self, context: &str) -> Result<(), IDAError> {
+        match self {
+            FieldType::Primitive(_) => Ok(()),
+            FieldType::Existing(typ) => {
+                if unsafe { idalib_is_valid_type_ordinal(typ.ordinal()) } {
+                    Ok(())
+                } else {
+                    Err(IDAError::not_found_with(format!(
+                        "Type #{} used in {} does not exist",
+                        typ.ordinal(),
+                        context
+                    )))
+                }
+            }
+            FieldType::ForwardRef(_) => Err(IDAError::unsupported(format!(
+                "Forward references not supported in {}",
+                context
+            ))),
+            FieldType::Inline(inner) => {
+                inner.validate_members()?;
+                inner.check_members()
+            }
+            FieldType::Pointer(target) => target.check_resolvable(context),
+            FieldType::Array(element, _) => element.check_resolvable(context),
+            FieldType::Declared(expr) if expr.trim().is_empty() => Err(IDAError::validation(
+                format!("Empty type expression used in {context}"),
+            )),
+            FieldType::Declared(_) => Ok(()),
+        }
+    }
+
+    /// Look up an existing type by name, or parse `name` as a C type
+    ///
+    /// Unlike the `From` conversions this needs the database, so there is no
+    /// `From<&str>`; use this to refer to a type defined earlier:
+    ///
+    /// ```ignore
+    /// builders::struct_type("List")
+    ///     .field("head", FieldType::from_name(&idb, "Node*")?)
+    ///     .field("tag", FieldType::from_name(&idb, "Tag")?)
+    /// ```
+    ///
+    /// Use `FieldType::forward` instead for a type that is not built yet.
+    /// Parsing adds the type to the type library, so it fails on a read-only
+    /// database.
+    pub fn from_name(idb: &IDB, name: &str) -> Result<FieldType, IDAError> {
+        if let Some(typ) = idb.get_type_by_name(name) {
+            return Ok(FieldType::Existing(typ));
+        }
+        ensure_writable()?;
+        match parse_type_expr(name) {
+            0 => Err(IDAError::not_found_with(format!("Unknown type '{name}'"))),
+            ordinal => Ok(FieldType::Existing(Type::from_ordinal(ordinal))),
+        }
+    }
+
+    /// Refer by name to a type being built (see `FieldType::ForwardRef`)
+    pub fn forward(name: impl Into<String>) -> FieldType {
+        FieldType::ForwardRef(name.into())
+    }
+
+    /// Start building an array of `num_elements` of this type
+    ///
+    /// `builders::int32().array(10).build()?` builds `int32_t[10]`.
+    pub fn array(self, num_elements: u32) -> ArrayBuilder {
+        ArrayBuilder::new(self, num_elements)
+    }
+
+    /// Start building a pointer to this type
+    pub fn ptr(self) -> PointerBuilder {
+        PointerBuilder::new(self)
+    }
+
+    /// Start building a pointer to a pointer to this type
+    pub fn ptr_ptr(self) -> PointerBuilder {
+        PointerBuilder::new(FieldType::Pointer(Box::new(self)))
+    }
 }
 
 /// Primitive types available in IDA
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum PrimitiveType {
     Void,
     Int8,
     Int16,
     Int32,
     Int64,
+    Int128,
     UInt8,
     UInt16,
     UInt32,
     UInt64,
+    UInt128,
+    /// 16-bit float, the `_Float16` typedef of 2-byte storage; IDA's own
+    /// special float (`BTMT_SPECFLT`) is the processor's tbyte on x86
+    Float16,
     Float,
     Double,
+    /// `long double`; its size follows the database compiler's `cc.size_ldbl`
+    LongDouble,
+    /// Plain `char`, whose signedness is implementation-defined
     Char,
+    /// Explicitly `signed char`
+    SignedChar,
+    /// `wchar_t`, a typedef of `Char16` for compilers targeting Windows and
+    /// of `Char32` otherwise, following the database's compiler
+    WChar,
+    Char16,
+    Char32,
     Bool,
 }
 
 impl PrimitiveType {
-    /// Get the IDA basic type code
-    fn to_ida_type(self) -> u32 {
+    /// Get the IDA basic type code (`BTF_*` value, base type and modifiers)
+    ///
+    /// `Float16` has no code: IDA has no basic half float, so it is only
+    /// available as the `_Float16` typedef.
+    pub fn to_ida_type(self) -> Option<u32> {
+        let code = match self {
+            PrimitiveType::Void => 0x01,    // BTF_VOID
+            PrimitiveType::Int8 => 0x12,    // BTF_INT8 (BT_INT8 | BTMT_SIGNED)
+            PrimitiveType::Int16 => 0x13,   // BTF_INT16 (BT_INT16 | BTMT_SIGNED)
+            PrimitiveType::Int32 => 0x14,   // BTF_INT32 (BT_INT32 | BTMT_SIGNED)
+            PrimitiveType::Int64 => 0x15,   // BTF_INT64 (BT_INT64 | BTMT_SIGNED)
+            PrimitiveType::Int128 => 0x16,  // BTF_INT128 (BT_INT128 | BTMT_SIGNED)
+            PrimitiveType::UInt8 => 0x22,   // BTF_UINT8 (BT_INT8 | BTMT_USIGNED)
+            PrimitiveType::UInt16 => 0x23,  // BTF_UINT16 (BT_INT16 | BTMT_USIGNED)
+            PrimitiveType::UInt32 => 0x24,  // BTF_UINT32 (BT_INT32 | BTMT_USIGNED)
+            PrimitiveType::UInt64 => 0x25,  // BTF_UINT64 (BT_INT64 | BTMT_USIGNED)
+            PrimitiveType::UInt128 => 0x26, // BTF_UINT128 (BT_INT128 | BTMT_USIGNED)
+            PrimitiveType::Bool => 0x08,    // BTF_BOOL (BT_BOOL | BTMT_DEFBOOL)
+            PrimitiveType::Float16 => return None,
+            PrimitiveType::Float => 0x09,   // BTF_FLOAT (BT_FLOAT | BTMT_FLOAT)
+            PrimitiveType::Double => 0x19,  // BTF_DOUBLE (BT_FLOAT | BTMT_DOUBLE)
+            PrimitiveType::LongDouble => 0x29, // BTF_LDOUBLE (BT_FLOAT | BTMT_LNGDBL)
+            PrimitiveType::Char => 0x32,    // BTF_CHAR (BT_INT8 | BTMT_CHAR)
+            PrimitiveType::SignedChar => 0x12, // BTF_INT8 (BT_INT8 | BTMT_SIGNED)
+            PrimitiveType::WChar => match get_wchar_size() {
+                4 => 0x34, // BT_INT32 | BTMT_CHAR
+                _ => 0x33, // BT_INT16 | BTMT_CHAR
+            },
+            PrimitiveType::Char16 => 0x33,  // BT_INT16 | BTMT_CHAR
+            PrimitiveType::Char32 => 0x34,  // BT_INT32 | BTMT_CHAR
+        };
+        Some(code)
+    }
+
+    /// Get the primitive for an IDA basic type code (`BTF_*` value)
+    ///
+    /// `SignedChar` shares its code with `Int8` and decodes as the latter.
+    /// `WChar` and `Float16` have no code of their own, since they are the
+    /// named `wchar_t` and `_Float16` typedefs.
+    pub fn from_ida_type(code: u32) -> Option<PrimitiveType> {
+        let prim = match code {
+            0x01 => PrimitiveType::Void,
+            0x12 => PrimitiveType::Int8,
+            0x13 => PrimitiveType::Int16,
+            0x14 => PrimitiveType::Int32,
+            0x15 => PrimitiveType::Int64,
+            0x16 => PrimitiveType::Int128,
+            0x22 => PrimitiveType::UInt8,
+            0x23 => PrimitiveType::UInt16,
+            0x24 => PrimitiveType::UInt32,
+            0x25 => PrimitiveType::UInt64,
+            0x26 => PrimitiveType::UInt128,
+            0x08 => PrimitiveType::Bool,
+            0x09 => PrimitiveType::Float,
+            0x19 => PrimitiveType::Double,
+            0x29 => PrimitiveType::LongDouble,
+            0x32 => PrimitiveType::Char,
+            0x33 => PrimitiveType::Char16,
+            0x34 => PrimitiveType::Char32,
+            _ => return None,
+        };
+        Some(prim)
+    }
+
+    /// Get the size of this primitive in bytes
+    ///
+    /// Returns `None` for `void` and for primitives whose size depends on the
+    /// database's compiler or processor (`bool`, `long double`, `wchar_t`).
+    pub fn width(self) -> Option<u64> {
         match self {
-            PrimitiveType::Void => 0x00,    // BT_VOID
-            PrimitiveType::Int8 => 0x01,    // BT_INT8
-            PrimitiveType::Int16 => 0x02,   // BT_INT16
-            PrimitiveType::Int32 => 0x03,   // BT_INT32
-            PrimitiveType::Int64 => 0x04,   // BT_INT64
-            PrimitiveType::UInt8 => 0x05,   // BT_INT8 | BTMT_UNSIGNED
-            PrimitiveType::UInt16 => 0x06,  // BT_INT16 | BTMT_UNSIGNED
-            PrimitiveType::UInt32 => 0x07,  // BT_INT32 | BTMT_UNSIGNED
-            PrimitiveType::UInt64 => 0x08,  // BT_INT64 | BTMT_UNSIGNED
-            PrimitiveType::Bool => 0x08,    // BT_BOOL
-            PrimitiveType::Float => 0x09,   // BT_FLOAT
-            PrimitiveType::Double => 0x0A,  // BT_DOUBLE
-            PrimitiveType::Char => 0x01,    // BT_INT8 (char is typically signed byte)
+            PrimitiveType::Void
+            | PrimitiveType::Bool
+            | PrimitiveType::LongDouble
+            | PrimitiveType::WChar => None,
+            PrimitiveType::Int8
+            | PrimitiveType::UInt8
+            | PrimitiveType::Char
+            | PrimitiveType::SignedChar => Some(1),
+            PrimitiveType::Int16
+            | PrimitiveType::UInt16
+            | PrimitiveType::Float16
+            | PrimitiveType::Char16 => Some(2),
+            PrimitiveType::Int32
+            | PrimitiveType::UInt32
+            | PrimitiveType::Float
+            | PrimitiveType::Char32 => Some(4),
+            PrimitiveType::Int64 | PrimitiveType::UInt64 | PrimitiveType::Double => Some(8),
+            PrimitiveType::Int128 | PrimitiveType::UInt128 => Some(16),
+        }
+    }
+
+    /// Whether this is a signed integer type
+    fn is_signed(self) -> bool {
+        matches!(
+            self,
+            PrimitiveType::Int8
+                | PrimitiveType::Int16
+                | PrimitiveType::Int32
+                | PrimitiveType::Int64
+                | PrimitiveType::Int128
+                | PrimitiveType::SignedChar
+        )
+    }
+
+    /// Get the ordinal of this primitive; `WChar` and `Float16` are the
+    /// `wchar_t` and `_Float16` typedefs, created if the database has none
+    fn ordinal(self) -> u32 {
+        match (self, self.to_ida_type()) {
+            (PrimitiveType::WChar, Some(code)) => get_or_create_primitive_typedef("wchar_t", code),
+            (_, Some(code)) => get_primitive_type_ordinal(code),
+            // A typedef of 2-byte storage (BTF_UINT16)
+            (_, None) => get_or_create_primitive_typedef("_Float16", 0x23),
         }
     }
 
     /// Create a Type from this primitive
     pub fn to_type(self) -> Result<Type, IDAError> {
-        let ordinal = get_primitive_type_ordinal(self.to_ida_type());
+        let ordinal = self.ordinal();
         if ordinal == 0 {
-            return Err(IDAError::ffi_with("Failed to create primitive type"));
+            return Err(ffi_error("Failed to create primitive type"));
         }
         Ok(Type::from_ordinal(ordinal))
     }
+
+    /// Start building an array of `num_elements` of this primitive
+    pub fn array(self, num_elements: u32) -> ArrayBuilder {
+        FieldType::from(self).array(num_elements)
+    }
+
+    /// Start building a pointer to this primitive
+    pub fn ptr(self) -> PointerBuilder {
+        FieldType::from(self).ptr()
+    }
+
+    /// Start building a pointer to a pointer to this primitive
+    pub fn ptr_ptr(self) -> PointerBuilder {
+        FieldType::from(self).ptr_ptr()
+    }
 }
 
 impl StructBuilder {
@@ -119,29 +806,75 @@ impl StructBuilder {
             fields: Vec::new(),
             bitfields: Vec::new(),
             is_union: false,
+            pack: None,
+            align: None,
+            fill_gaps: false,
+            total_size: None,
+            no_tail_padding: false,
+            is_class: false,
+            virtual_methods: Vec::new(),
+            replace_existing: false,
+            merge_into_existing: false,
+            next_auto_bit: None,
         }
     }
 
     /// Create a new union builder
     pub fn new_union(name: impl Into<String>) -> Self {
         Self {
-            name: name.into(),
-            fields: Vec::new(),
-            bitfields: Vec::new(),
             is_union: true,
+            ..Self::new(name)
         }
     }
 
     /// Add a field to the struct
     pub fn field(mut self, name: impl Into<String>, field_type: impl Into<FieldType>) -> Self {
+        self.next_auto_bit = None;
         self.fields.push(StructField {
             name: name.into(),
             field_type: field_type.into(),
             offset: None,
+            comment: None,
+            is_flexible: false,
+            is_vftable: false,
+            is_baseclass: false,
         });
         self
     }
 
+    /// Add a field with a comment
+    pub fn field_with_comment(
+        self,
+        name: impl Into<String>,
+        field_type: impl Into<FieldType>,
+        comment: impl Into<String>,
+    ) -> Self {
+        self.field(name, field_type).comment(comment)
+    }
+
+    /// Attach a comment to the most recently added field
+    ///
+    /// An empty comment leaves the field without one.
+    pub fn comment(self, comment: impl Into<String>) -> Self {
+        self.set_last_comment(comment.into(), false)
+    }
+
+    /// Attach a repeatable comment to the most recently added field
+    pub fn repeatable_comment(self, comment: impl Into<String>) -> Self {
+        self.set_last_comment(comment.into(), true)
+    }
+
+    fn set_last_comment(mut self, comment: String, repeatable: bool) -> Self {
+        if let Some(field) = self.fields.last_mut() {
+            field.comment = if comment.is_empty() {
+                None
+            } else {
+                Some((comment, repeatable))
+            };
+        }
+        self
+    }
+
     /// Add a field with explicit offset (for structs only)
     pub fn field_at(
         mut self,
@@ -153,10 +886,35 @@ impl StructBuilder {
             // Unions don't use explicit offsets
             return self.field(name, field_type);
         }
+        self.next_auto_bit = None;
         self.fields.push(StructField {
             name: name.into(),
             field_type: field_type.into(),
             offset: Some(offset),
+            comment: None,
+            is_flexible: false,
+            is_vftable: false,
+            is_baseclass: false,
+        });
+        self
+    }
+
+    /// Add a flexible array member (`elem_type name[]`), which must be the
+    /// last field of a struct
+    pub fn flexible_array_field(
+        mut self,
+        name: impl Into<String>,
+        elem_type: impl Into<FieldType>,
+    ) -> Self {
+        self.next_auto_bit = None;
+        self.fields.push(StructField {
+            name: name.into(),
+            field_type: elem_type.into(),
+            offset: None,
+            comment: None,
+            is_flexible: true,
+            is_vftable: false,
+            is_baseclass: false,
         });
         self
     }
@@ -166,27 +924,210 @@ impl StructBuilder {
         self.is_union = is_union;
         self
     }
+
+    /// Set the maximum member alignment, like `#pragma pack(alignment)`
+    ///
+    /// `pack(1)` lays out members without any padding between them.
+    pub fn pack(mut self, alignment: u32) -> Self {
+        self.pack = Some(alignment);
+        self
+    }
+
+    /// Set the declared alignment of the struct, like `__declspec(align(alignment))`
+    pub fn align(mut self, alignment: u32) -> Self {
+        self.align = Some(alignment);
+        self
+    }
+
+    /// Mark the type as a C++ class, which IDA treats as an object with
+    /// constructors and virtual methods rather than plain data
+    pub fn as_class(mut self) -> Self {
+        self.is_class = true;
+        self
+    }
+
+    /// Add a virtual method of type `function_type` to the class's vtable
+    ///
+    /// The first virtual method makes the type a class and inserts a
+    /// `__vftable` pointer as its first member. The vtable is built along
+    /// with the class as a `<name>_vtbl` struct holding a pointer to each
+    /// method, in the order they were added.
+    pub fn virtual_method(mut self, name: impl Into<String>, function_type: Type) -> Self {
+        if !self.fields.iter().any(|field| field.is_vftable) {
+            // The vtable pointer follows the base class, if any
+            let position = self.fields.iter().take_while(|field| field.is_baseclass).count();
+            self.insert_field(position, StructField {
+                name: "__vftable".to_owned(),
+                // Replaced by a pointer to the vtable once it is built
+                field_type: FieldType::Pointer(Box::new(FieldType::Primitive(PrimitiveType::Void))),
+                offset: None,
+                comment: None,
+                is_flexible: false,
+                is_vftable: true,
+                is_baseclass: false,
+            });
+        }
+        self.is_class = true;
+        self.virtual_methods.push(VirtualMethod {
+            name: name.into(),
+            function_type,
+        });
+        self
+    }
+
+    /// Insert a field ahead of the ones added so far, keeping it ahead of the
+    /// bitfields added after them too
+    fn insert_field(&mut self, position: usize, field: StructField) {
+        self.fields.insert(position, field);
+        for bitfield in &mut self.bitfields {
+            if let Some(before) = bitfield.fields_before.as_mut()
+                && *before >= position
+            {
+                *before += 1;
+            }
+        }
+    }
+
+    /// Derive from the struct `base`, which becomes a base class member at
+    /// the start of the type so the decompiler can upcast to it
+    ///
+    /// Only single inheritance is supported. Fields added with `field` are
+    /// placed after the base.
+    pub fn base_class(mut self, base: Type) -> Self {
+        self.next_auto_bit = None;
+        self.insert_field(0, StructField {
+            name: "baseclass_0".to_owned(),
+            field_type: FieldType::Existing(base),
+            offset: None,
+            comment: None,
+            is_flexible: false,
+            is_vftable: false,
+            is_baseclass: true,
+        });
+        self
+    }
+
+    /// Redefine an existing type of the same name instead of failing
+    ///
+    /// The existing type keeps its ordinal, so types and addresses that refer
+    /// to it see the new definition. This lets a script that defines its types
+    /// be run more than once against the same database. If the build fails,
+    /// the old definition is put back. The `<name>_vtbl` struct of a class
+    /// with virtual methods is replaced along with it.
+    pub fn replace_existing(mut self, replace: bool) -> Self {
+        self.replace_existing = replace;
+        self
+    }
+
+    /// Add the members to an existing type of the same name instead of
+    /// failing
+    ///
+    /// The existing members are kept at their offsets, with their comments
+    /// and base class or vtable pointer roles, and the type keeps its packing
+    /// and alignment unless the builder sets them. Members of the builder
+    /// that match an existing member by name and type are skipped. Other
+    /// members are added as usual, except that members without an offset go
+    /// after the existing ones; such a member needs a known size, so a
+    /// forward reference or declared type embedded by value needs an
+    /// explicit offset. A member that has the name of an existing member but
+    /// a different type or offset, or that overlaps another member, is an
+    /// error. Like `replace_existing`, the type keeps its ordinal.
+    pub fn merge_into_existing(mut self, merge: bool) -> Self {
+        self.merge_into_existing = merge;
+        self
+    }
+
+    /// Don't pad the size of the struct up to a multiple of its alignment
+    ///
+    /// `{ u32; u8 }` is then 5 bytes rather than 8. Members are still aligned
+    /// as usual, so combine this with `pack(1)` for a fully unpadded layout.
+    /// A size set with `size()` is kept either way, since it is reached with
+    /// an explicit padding member.
+    pub fn no_tail_padding(mut self, no_tail_padding: bool) -> Self {
+        self.no_tail_padding = no_tail_padding;
+        self
+    }
+
+    /// Fill holes left before explicitly placed fields with `gap_N` byte arrays
+    ///
+    /// Off by default, in which case IDA leaves the holes undefined. Padding
+    /// inserted to align automatically placed fields is not affected.
+    pub fn fill_gaps(mut self, fill_gaps: bool) -> Self {
+        self.fill_gaps = fill_gaps;
+        self
+    }
+
+    /// Pad the type to `total_bytes` with a trailing `gap_N` byte array
+    ///
+    /// Useful when the size is known but only some of the fields are. The
+    /// size should be a multiple of the type's alignment, otherwise IDA rounds
+    /// it up; building fails if a field extends past it.
+    pub fn size(mut self, total_bytes: u64) -> Self {
+        self.total_size = Some(total_bytes);
+        self
+    }
     
     /// Add a bitfield to the struct
     pub fn bitfield(
-        mut self,
+        self,
+        name: impl Into<String>,
+        bit_offset: u32,
+        bit_width: u32,
+        is_unsigned: bool,
+    ) -> Self {
+        self.push_bitfield(name.into(), bit_offset, bit_width, is_unsigned, None)
+    }
+
+    /// Add a bitfield declared over a storage type of `storage_bytes` bytes
+    /// (1, 2, 4 or 8)
+    ///
+    /// The storage type determines the size and alignment the bitfield
+    /// contributes, so `uint8_t x : 3` gives a 1-byte struct where
+    /// `uint32_t x : 3` gives a 4-byte one.
+    pub fn bitfield_with_storage(
+        self,
         name: impl Into<String>,
         bit_offset: u32,
         bit_width: u32,
         is_unsigned: bool,
+        storage_bytes: u32,
+    ) -> Self {
+        self.push_bitfield(name.into(), bit_offset, bit_width, is_unsigned, Some(storage_bytes))
+    }
+
+    fn push_bitfield(
+        mut self,
+        name: String,
+        bit_offset: u32,
+        bit_width: u32,
+        is_unsigned: bool,
+        storage_bytes: Option<u32>,
     ) -> Self {
         if self.is_union {
             // Unions don't support bitfields in the same way
             return self;
         }
         self.bitfields.push(BitfieldInfo {
-            name: name.into(),
+            name,
             bit_offset,
             bit_width,
             is_unsigned,
+            storage_bytes,
+            fields_before: Some(self.fields.len()),
         });
+        self.next_auto_bit = Some(bit_offset + bit_width);
         self
     }
+
+    /// Add a bitfield right after the previously added one
+    ///
+    /// Consecutive bitfields are packed starting at bit 0; after a regular
+    /// field, packing restarts at the first byte past the members added so
+    /// far.
+    pub fn auto_bitfield(self, name: impl Into<String>, bit_width: u32, is_unsigned: bool) -> Self {
+        let bit_offset = self.next_auto_bit.unwrap_or_else(|| self.next_free_bit());
+        self.bitfield(name, bit_offset, bit_width, is_unsigned)
+    }
     
     /// Add an unsigned bitfield (convenience method)
     pub fn unsigned_bitfield(
@@ -208,42 +1149,439 @@ impl StructBuilder {
         self.bitfield(name, bit_offset, bit_width, false)
     }
     
+    /// Add an anonymous struct member, whose fields are accessed as if they
+    /// were members of this type
+    ///
+    /// ```ignore
+    /// builders::union_type("Value").anonymous_struct(|s| {
+    ///     s.field("lo", builders::uint32()).field("hi", builders::uint32())
+    /// })
+    /// ```
+    pub fn anonymous_struct(self, define: impl FnOnce(StructBuilder) -> StructBuilder) -> Self {
+        let inner = define(StructBuilder::new(String::new()));
+        self.field(String::new(), FieldType::Inline(Box::new(inner)))
+    }
+
+    /// Add an anonymous union member, whose fields are accessed as if they
+    /// were members of this type
+    pub fn anonymous_union(self, define: impl FnOnce(StructBuilder) -> StructBuilder) -> Self {
+        let inner = define(StructBuilder::new_union(String::new()));
+        self.field(String::new(), FieldType::Inline(Box::new(inner)))
+    }
+
+    /// Add a named member whose type is a union defined in place
+    ///
+    /// The closure receives an empty union builder, so the fields added to
+    /// it overlap. Use `anonymous_union` for a member without a name.
+    ///
+    /// ```ignore
+    /// builders::struct_type("Variant")
+    ///     .field("tag", builders::uint32())
+    ///     .union_field("value", |u| {
+    ///         u.field("i", builders::int32()).field("f", builders::float())
+    ///     })
+    /// ```
+    pub fn union_field(
+        self,
+        name: impl Into<String>,
+        define: impl FnOnce(StructBuilder) -> StructBuilder,
+    ) -> Self {
+        let inner = define(StructBuilder::new_union(String::new()));
+        self.field(name, FieldType::Inline(Box::new(inner)))
+    }
+    
+    /// Get the name of the type being built; empty for anonymous members
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Get the number of members added so far, bitfields included
+    pub fn field_count(&self) -> usize {
+        self.fields.len() + self.bitfields.len()
+    }
+
+    /// Get the names of the members added so far, in the order `build` adds
+    /// them: regular fields first, then bitfields
+    ///
+    /// Anonymous struct/union members have an empty name.
+    pub fn field_names(&self) -> Vec<&str> {
+        self.fields
+            .iter()
+            .map(|field| field.name.as_str())
+            .chain(self.bitfields.iter().map(|bitfield| bitfield.name.as_str()))
+            .collect()
+    }
+
+    /// Get the size the type will have once built, laid out the same way
+    /// `build` lays it out, including tail padding and any `size()`
+    ///
+    /// Returns `None` if a member's type is opaque or only forward declared.
+    pub fn computed_size(&self) -> Option<u64> {
+        self.type_layout().map(|(size, _)| size)
+    }
+
+    /// Build the type, registering it in `registry` before its members are
+    /// added so that other types can point back at it
+    ///
+    /// Forward references to types not in the registry yet create empty
+    /// placeholder structs, which are filled in when those types are built
+    /// into the same registry.
+    ///
+    /// If the build fails, the types it created, including helper types such
+    /// as pointers and gap arrays, are deleted again and the registry is left
+    /// as it was.
+    pub fn build_into(self, registry: &mut TypeRegistry) -> Result<Type, IDAError> {
+        TypeValidator::validate(&self)?;
+        let builder = self.merged_with_existing()?;
+        let snapshot = registry.snapshot();
+        builder
+            .create_registered(registry)
+            .and_then(|struct_ordinal| builder.populate(struct_ordinal, Some(&mut *registry)))
+            .inspect_err(|_| registry.rollback(snapshot))
+    }
+
     /// Add a self-referential field (pointer to this struct)
     /// Useful for linked lists, trees, etc.
     pub fn self_ref(self, name: impl Into<String>) -> Self {
         let struct_name = self.name.clone();
-        self.field(name, FieldType::ForwardRef(struct_name))
+        self.field(
+            name,
+            FieldType::Pointer(Box::new(FieldType::ForwardRef(struct_name))),
+        )
     }
 }
 
-impl TypeValidator for StructBuilder {
-    fn validate(&self) -> Result<(), IDAError> {
-        // Check for empty name
-        if self.name.is_empty() {
-            return Err(IDAError::ffi_with("Struct/union name cannot be empty"));
-        }
-        
-        // Check for duplicate field names
+impl StructBuilder {
+    /// Names visible as members of this type, including the members of
+    /// anonymous struct/union members
+    fn member_names(&self) -> Vec<&str> {
+        let mut names = Vec::new();
+        for field in &self.fields {
+            match &field.field_type {
+                FieldType::Inline(inner) if field.name.is_empty() => {
+                    names.extend(inner.member_names());
+                }
+                _ => names.push(field.name.as_str()),
+            }
+        }
+        names.extend(self.bitfields.iter().map(|b| b.name.as_str()));
+        names
+    }
+
+    /// Offset and size of each field, placed the same way `build` places
+    /// them; the size is `None` for opaque field types, and so is the offset
+    /// of any field placed automatically after one
+    fn field_extents(&self) -> Vec<(Option<u64>, Option<u64>)> {
+        let mut extents = Vec::with_capacity(self.fields.len());
+        let mut current_offset = Some(0u64);
+        for (index, field) in self.fields.iter().enumerate() {
+            // Fields added after bitfields start past their storage
+            for bitfield in &self.bitfields {
+                if !self.is_union && bitfield.fields_before.is_some_and(|before| before <= index) {
+                    current_offset =
+                        current_offset.map(|offset| offset.max(bitfield.storage_end()));
+                }
+            }
+            let layout = match field.field_type.layout() {
+                // Flexible array members take no space
+                Some((_, align)) if field.is_flexible => Some((0, align)),
+                // Empty base classes take no space either
+                None if field.is_baseclass => Some((0, 1)),
+                layout => layout,
+            };
+            let field_align = match layout {
+                Some((_, align)) => self.pack.map_or(align, |pack| align.min(pack as u64)),
+                None => 1,
+            };
+            let offset = field
+                .offset
+                .or_else(|| current_offset.map(|offset| offset.next_multiple_of(field_align)));
+            let size = layout.map(|(size, _)| size);
+            if !self.is_union && field.offset.is_none() {
+                current_offset = offset.zip(size).map(|(offset, size)| offset + size);
+            }
+            extents.push((offset, size));
+        }
+        extents
+    }
+
+    /// First bit past the fields and bitfields added so far, rounded up to a
+    /// whole byte
+    fn next_free_bit(&self) -> u32 {
+        let fields_end = self
+            .field_extents()
+            .iter()
+            .map(|(offset, size)| offset.unwrap_or(0) + size.unwrap_or(0))
+            .max()
+            .unwrap_or(0);
+        let bitfields_end = self
+            .bitfields
+            .iter()
+            .map(|b| (b.bit_offset + b.bit_width).div_ceil(8) as u64)
+            .max()
+            .unwrap_or(0);
+        (fields_end.max(bitfields_end) * 8) as u32
+    }
+
+    /// Size and alignment the built type is expected to have, or `None` if
+    /// any member is opaque
+    fn type_layout(&self) -> Option<(u64, u64)> {
+        let mut end = 0u64;
+        let mut align = 1u64;
+        for (field, (offset, size)) in self.fields.iter().zip(self.field_extents()) {
+            let (_, field_align) = field.field_type.layout()?;
+            end = end.max(offset? + size?);
+            align = align.max(self.pack.map_or(field_align, |pack| field_align.min(pack as u64)));
+        }
+        for bitfield in &self.bitfields {
+            end = end.max(bitfield.storage_end());
+            align = align.max(bitfield.storage_bytes.unwrap_or(1) as u64);
+        }
+        if let Some(total_size) = self.total_size {
+            end = end.max(total_size);
+        }
+        if let Some(declared) = self.align {
+            align = align.max(declared as u64);
+        }
+        if self.no_tail_padding {
+            return Some((end, align));
+        }
+        Some((end.next_multiple_of(align), align))
+    }
+
+    /// Insert `gap_N: uint8[size]` fields into the holes before explicitly
+    /// placed fields
+    fn insert_gap_fields(
+        &mut self,
+        gap_names: &mut impl Iterator<Item = String>,
+    ) -> Result<(), IDAError> {
+        let extents = self.field_extents();
+        let mut end = Some(0u64);
+        let mut fields = Vec::with_capacity(self.fields.len());
+        // New position of each field, to keep bitfields after the same fields
+        let mut positions = Vec::with_capacity(self.fields.len() + 1);
+        for (field, (offset, size)) in std::mem::take(&mut self.fields).into_iter().zip(extents) {
+            if let Some(gap_start) = end
+                && let Some(offset) = field.offset
+                && offset > gap_start
+            {
+                let gap_len = (offset - gap_start) as u32;
+                let gap_type = ArrayBuilder::new(PrimitiveType::UInt8, gap_len).build()?;
+                fields.push(StructField {
+                    name: gap_names.next().unwrap_or_default(),
+                    field_type: FieldType::Existing(gap_type),
+                    offset: Some(gap_start),
+                    comment: None,
+                    is_flexible: false,
+                    is_vftable: false,
+                    is_baseclass: false,
+                });
+            }
+            end = match (end, offset, size) {
+                (Some(end), Some(offset), Some(size)) => Some(end.max(offset + size)),
+                _ => None,
+            };
+            positions.push(fields.len());
+            fields.push(field);
+        }
+        positions.push(fields.len());
+        for bitfield in &mut self.bitfields {
+            if let Some(before) = bitfield.fields_before.as_mut() {
+                *before = positions[(*before).min(positions.len() - 1)];
+            }
+        }
+        self.fields = fields;
+        Ok(())
+    }
+
+    /// `gap_N` names not used by any member, for synthetic padding fields
+    fn gap_names(&self) -> impl Iterator<Item = String> + use<> {
+        let taken: std::collections::HashSet<String> =
+            self.member_names().into_iter().map(str::to_owned).collect();
+        (0..)
+            .map(|index| format!("gap_{}", index))
+            .filter(move |name| !taken.contains(name))
+    }
+
+    /// Display name for error messages; inline types have no name of their own
+    fn display_name(&self) -> &str {
+        if self.name.is_empty() {
+            if self.is_union { "<anonymous union>" } else { "<anonymous struct>" }
+        } else {
+            &self.name
+        }
+    }
+
+    /// Validate everything except the type name
+    fn validate_members(&self) -> Result<(), IDAError> {
+        // Check for duplicate field names, including names brought in by
+        // anonymous members
         let mut field_names = std::collections::HashSet::new();
         for field in &self.fields {
-            if !field_names.insert(&field.name) {
-                return Err(IDAError::ffi_with(format!(
-                    "Duplicate field name '{}' in {}",
+            let names = match &field.field_type {
+                FieldType::Inline(inner) if field.name.is_empty() => inner.member_names(),
+                _ => vec![field.name.as_str()],
+            };
+            for name in names {
+                if !field_names.insert(name) {
+                    return Err(IDAError::validation(format!(
+                        "Duplicate field name '{}' in {}",
+                        name,
+                        self.display_name()
+                    )));
+                }
+            }
+        }
+        
+        // A flexible array member ends a struct with other members
+        if let Some(position) = self.fields.iter().position(|f| f.is_flexible) {
+            let field = &self.fields[position];
+            if self.is_union {
+                return Err(IDAError::validation(format!(
+                    "Flexible array member '{}' is not allowed in union {}",
+                    field.name,
+                    self.display_name()
+                )));
+            }
+            if position + 1 != self.fields.len() {
+                return Err(IDAError::validation(format!(
+                    "Flexible array member '{}' must be the last field of {}",
+                    field.name,
+                    self.display_name()
+                )));
+            }
+            if position == 0 {
+                return Err(IDAError::validation(format!(
+                    "Flexible array member '{}' needs a preceding field in {}",
+                    field.name,
+                    self.display_name()
+                )));
+            }
+        }
+        
+        // Single inheritance only, from the first member of a struct
+        match self.fields.iter().filter(|field| field.is_baseclass).count() {
+            0 => {}
+            1 if self.is_union => {
+                return Err(IDAError::validation(format!(
+                    "{} cannot have a base class",
+                    self.display_name()
+                )));
+            }
+            1 => {}
+            _ => {
+                return Err(IDAError::validation(format!(
+                    "{} has more than one base class; only single inheritance is supported",
+                    self.display_name()
+                )));
+            }
+        }
+        
+        // Virtual methods go in a vtable named after the class
+        if !self.virtual_methods.is_empty() {
+            if self.is_union || self.name.is_empty() {
+                return Err(IDAError::validation(format!(
+                    "{} cannot have virtual methods",
+                    self.display_name()
+                )));
+            }
+            let mut method_names = std::collections::HashSet::new();
+            for method in &self.virtual_methods {
+                if !method_names.insert(method.name.as_str()) {
+                    return Err(IDAError::validation(format!(
+                        "Duplicate virtual method '{}' in {}",
+                        method.name, self.name
+                    )));
+                }
+            }
+        }
+        
+        // A type cannot contain itself by value
+        for field in &self.fields {
+            if let Some(name) = field.field_type.embedded_ref()
+                && name == self.name
+            {
+                return Err(IDAError::validation(format!(
+                    "Field '{}' embeds {} in itself, which would give it infinite size (use a pointer, e.g. `self_ref`)",
                     field.name, self.name
                 )));
             }
         }
         
+        // Inline types are validated along with the type that contains them
+        for field in &self.fields {
+            if let FieldType::Inline(inner) = &field.field_type {
+                inner.validate_members()?;
+            }
+        }
+        
+        // Packing and alignment must be powers of two
+        for (what, value) in [("pack", self.pack), ("alignment", self.align)] {
+            if let Some(value) = value
+                && !value.is_power_of_two()
+            {
+                return Err(IDAError::validation(format!(
+                    "Invalid {} {} for {}. Must be a power of two",
+                    what,
+                    value,
+                    self.display_name()
+                )));
+            }
+        }
+        
+        // A bare void member has no size; only void pointers make sense
+        for field in &self.fields {
+            if let FieldType::Primitive(PrimitiveType::Void) = field.field_type {
+                return Err(IDAError::validation(format!(
+                    "Field '{}' in {} cannot have type void (use a void pointer instead)",
+                    field.name,
+                    self.display_name()
+                )));
+            }
+        }
+        
         // Check for duplicate bitfield names
         for bitfield in &self.bitfields {
-            if !field_names.insert(&bitfield.name) {
-                return Err(IDAError::ffi_with(format!(
+            if !field_names.insert(bitfield.name.as_str()) {
+                return Err(IDAError::validation(format!(
                     "Duplicate bitfield name '{}' in {}",
-                    bitfield.name, self.name
+                    bitfield.name,
+                    self.display_name()
                 )));
             }
         }
         
+        // Bitfields must fit their declared storage type
+        for bitfield in &self.bitfields {
+            if let Some(storage) = bitfield.storage_bytes {
+                if !matches!(storage, 1 | 2 | 4 | 8) {
+                    return Err(IDAError::validation(format!(
+                        "Bitfield '{}' has invalid storage size {} (must be 1, 2, 4, or 8 bytes)",
+                        bitfield.name, storage
+                    )));
+                }
+                if bitfield.bit_width > storage * 8 {
+                    return Err(IDAError::validation(format!(
+                        "Bitfield '{}' is {} bits wide, more than its {}-byte storage type holds",
+                        bitfield.name, bitfield.bit_width, storage
+                    )));
+                }
+                // ...without crossing into the next storage unit
+                let unit_bits = storage * 8;
+                let last_bit = bitfield.bit_offset + bitfield.bit_width.max(1) - 1;
+                if bitfield.bit_offset / unit_bits != last_bit / unit_bits {
+                    return Err(IDAError::validation(format!(
+                        "Bitfield '{}' (bits {}-{}) crosses the boundary of its {}-byte storage unit at bit {}",
+                        bitfield.name,
+                        bitfield.bit_offset,
+                        last_bit + 1,
+                        storage,
+                        (bitfield.bit_offset / unit_bits + 1) * unit_bits
+                    )));
+                }
+            }
+        }
+        
         // Validate bitfield positions don't overlap
         let mut bit_ranges: Vec<(u32, u32)> = Vec::new();
         for bitfield in &self.bitfields {
@@ -255,7 +1593,7 @@ impl TypeValidator for StructBuilder {
                 if (start >= *existing_start && start < *existing_end) || 
                    (end > *existing_start && end <= *existing_end) ||
                    (start <= *existing_start && end >= *existing_end) {
-                    return Err(IDAError::ffi_with(format!(
+                    return Err(IDAError::validation(format!(
                         "Bitfield '{}' overlaps with another bitfield (bits {}-{})",
                         bitfield.name, start, end
                     )));
@@ -265,15 +1603,358 @@ impl TypeValidator for StructBuilder {
             bit_ranges.push((start, end));
         }
         
+        // Fields must fit within an explicitly requested size
+        if let Some(total_size) = self.total_size {
+            for (field, (offset, size)) in self.fields.iter().zip(self.field_extents()) {
+                if let (Some(offset), Some(size)) = (offset, size)
+                    && offset + size > total_size
+                {
+                    return Err(IDAError::validation(format!(
+                        "Field '{}' (bytes {}-{}) extends past the {}-byte size of {}",
+                        field.name,
+                        offset,
+                        offset + size,
+                        total_size,
+                        self.display_name()
+                    )));
+                }
+            }
+        }
+        
+        // Fields must not share bytes, whether placed explicitly or automatically
+        if !self.is_union {
+            let extents = self.field_extents();
+            for (i, (field, &(start, size))) in self.fields.iter().zip(&extents).enumerate() {
+                let (Some(start), Some(size)) = (start, size) else { continue };
+                for (other, &(other_start, other_size)) in self.fields[..i].iter().zip(&extents) {
+                    let (Some(other_start), Some(other_size)) = (other_start, other_size) else {
+                        continue;
+                    };
+                    if start < other_start + other_size && other_start < start + size {
+                        return Err(IDAError::validation(format!(
+                            "Field '{}' (bytes {}-{}) overlaps field '{}' (bytes {}-{}) in {}",
+                            field.name,
+                            start,
+                            start + size,
+                            other.name,
+                            other_start,
+                            other_start + other_size,
+                            self.display_name()
+                        )));
+                    }
+                }
+            }
+
+            // ...nor with bitfields
+            for bitfield in &self.bitfields {
+                let bytes = (
+                    (bitfield.bit_offset / 8) as u64,
+                    (bitfield.bit_offset + bitfield.bit_width).div_ceil(8) as u64,
+                );
+                for (field, &(start, size)) in self.fields.iter().zip(&extents) {
+                    let (Some(start), Some(size)) = (start, size) else { continue };
+                    if bytes.0 < start + size && start < bytes.1 {
+                        return Err(IDAError::validation(format!(
+                            "Bitfield '{}' (bits {}-{}) overlaps field '{}' (bytes {}-{}) in {}",
+                            bitfield.name,
+                            bitfield.bit_offset,
+                            bitfield.bit_offset + bitfield.bit_width,
+                            field.name,
+                            start,
+                            start + size,
+                            self.display_name()
+                        )));
+                    }
+                }
+            }
+        }
+        
         Ok(())
     }
-}
 
-impl TypeBuilder for StructBuilder {
-    fn build(self) -> Result<Type, IDAError> {
-        // Validate before building
-        TypeValidator::validate(&self)?;
-        // Create the empty struct/union
+    /// Build a struct/union defined in place by another type
+    fn build_inline(self) -> Result<Type, IDAError> {
+        self.validate_members()?;
+        self.create()
+    }
+
+    /// Check that the member types can be resolved without a registry, as
+    /// `build` does
+    fn check_members(&self) -> Result<(), IDAError> {
+        for field in &self.fields {
+            let forward_ref = match &field.field_type {
+                FieldType::ForwardRef(name) => Some(name),
+                FieldType::Pointer(target) => match &**target {
+                    // Pointers to the type itself point at the type being built
+                    FieldType::ForwardRef(name) if name == &self.name => continue,
+                    FieldType::ForwardRef(name) => Some(name),
+                    _ => None,
+                },
+                _ => None,
+            };
+            match forward_ref {
+                Some(name) => {
+                    return Err(IDAError::not_found_with(format!(
+                        "Forward reference to '{}' cannot be resolved (build the types with a TypeRegistry)",
+                        name
+                    )));
+                }
+                None => field.field_type.check_resolvable("struct members")?,
+            }
+        }
+        Ok(())
+    }
+
+    /// Create the validated type in the type library
+    fn create(self) -> Result<Type, IDAError> {
+        let original = self.existing_ordinal().map(save_type_definition);
+        let limit = ordinal_limit();
+        let struct_ordinal = self.create_empty()?;
+        // Don't leave a partially built type or its helper types behind; a
+        // replaced type gets its old definition back, since other types may
+        // refer to it
+        self.populate(struct_ordinal, None).inspect_err(|_| {
+            if let Some(original) = &original {
+                restore_replaced_type(struct_ordinal, original);
+            }
+            delete_types_since(limit);
+        })
+    }
+
+    /// Ordinal of the type this builder replaces, if `replace_existing` is
+    /// set and a type of the same name exists
+    fn existing_ordinal(&self) -> Option<u32> {
+        if !self.replace_existing || self.name.is_empty() {
+            return None;
+        }
+        match get_type_ordinal_by_name(&self.name) {
+            0 => None,
+            ordinal => Some(ordinal),
+        }
+    }
+
+    /// Combine this builder with the members of the type it merges into, if
+    /// `merge_into_existing` is set and a type of the same name exists
+    ///
+    /// The result replaces the existing type, which keeps its ordinal.
+    fn merged_with_existing(mut self) -> Result<Self, IDAError> {
+        if !self.merge_into_existing || self.name.is_empty() {
+            return Ok(self);
+        }
+        let ordinal = match get_type_ordinal_by_name(&self.name) {
+            0 => return Ok(self),
+            ordinal => ordinal,
+        };
+        let Ok(count) = u32::try_from(get_udt_member_count(ordinal)) else {
+            return Err(IDAError::already_exists(format!(
+                "'{}' already exists and is not a struct or union",
+                self.name
+            )));
+        };
+        if is_union_type(ordinal) != self.is_union {
+            return Err(IDAError::validation(format!(
+                "Cannot merge a {} into the existing {} '{}'",
+                if self.is_union { "union" } else { "struct" },
+                if self.is_union { "struct" } else { "union" },
+                self.name
+            )));
+        }
+
+        let fields = std::mem::take(&mut self.fields);
+        let bitfields = std::mem::take(&mut self.bitfields);
+        // Keep the layout settings of the existing type unless the builder
+        // sets its own
+        let layout = get_udt_layout(ordinal);
+        let setting = |index: usize| layout.get(index).copied().unwrap_or(0);
+        let mut merged = StructBuilder {
+            replace_existing: true,
+            merge_into_existing: false,
+            next_auto_bit: None,
+            pack: self.pack.or((setting(0) != 0).then(|| setting(0))),
+            align: self.align.or((setting(1) != 0).then(|| setting(1))),
+            no_tail_padding: self.no_tail_padding || setting(2) & 1 != 0,
+            is_class: self.is_class || setting(2) & 2 != 0,
+            ..self
+        };
+
+        // Keep the existing members where they are, with their comments and
+        // roles, noting the bit range and the type (`None` for bitfields) of
+        // each. The members are fetched once per attribute rather than per
+        // member
+        let members = get_udt_member_names(ordinal)
+            .into_iter()
+            .zip(get_udt_member_offsets(ordinal))
+            .zip(get_udt_member_sizes(ordinal))
+            .zip(get_udt_member_bitfields(ordinal))
+            .zip(get_udt_member_comments(ordinal))
+            .zip(get_udt_member_roles(ordinal));
+        let mut existing = Vec::with_capacity(count as usize);
+        for (index, (((((name, offset_bits), size_bits), bitfield), comment), role)) in
+            (0u32..).zip(members)
+        {
+            existing.push(ExistingMember {
+                name: name.clone(),
+                member_type: None,
+                bits: (offset_bits, size_bits),
+            });
+            if bitfield >= 0 {
+                merged = merged.bitfield_with_storage(
+                    name,
+                    offset_bits as u32,
+                    size_bits as u32,
+                    bitfield == 1,
+                    get_udt_member_bitfield_storage(ordinal, index),
+                );
+                continue;
+            }
+
+            let steps = TypeStep::parse_all(describe_udt_member_type(ordinal, index));
+            let field_type = FieldType::from_steps(&steps, &mut |member_type| {
+                Some(FieldType::Existing(Type::from_ordinal(member_type)))
+            })
+            .ok_or_else(|| {
+                IDAError::ffi_with(format!(
+                    "Failed to read member '{name}' of '{}'",
+                    merged.name
+                ))
+            })?;
+            if let Some(member) = existing.last_mut() {
+                member.member_type = Some(steps);
+            }
+            // Comments are set back as stored, which keeps them as they were
+            merged.fields.push(StructField {
+                name,
+                field_type,
+                offset: (!merged.is_union).then_some(offset_bits / 8),
+                comment: (!comment.is_empty()).then_some((comment, true)),
+                is_flexible: false,
+                is_vftable: role == 2,
+                is_baseclass: role == 1,
+            });
+        }
+        if existing.len() != count as usize {
+            return Err(IDAError::ffi_with(format!(
+                "Failed to read the members of '{}'",
+                merged.name
+            )));
+        }
+
+        // New members without an offset go after the existing ones, in the
+        // order they were added
+        let mut next_offset = existing
+            .iter()
+            .map(|member| (member.bits.0 + member.bits.1).div_ceil(8))
+            .max()
+            .unwrap_or(0);
+        for (index, mut field) in fields.into_iter().enumerate() {
+            match existing.iter().find(|member| member.name == field.name) {
+                Some(member) => {
+                    let same_offset = field
+                        .offset
+                        .is_none_or(|offset| merged.is_union || offset * 8 == member.bits.0);
+                    let same_type = member
+                        .member_type
+                        .as_ref()
+                        .is_some_and(|steps| field.field_type.matches(steps));
+                    // The vtable pointer is pointed at the rebuilt vtable
+                    let same_vftable = field.is_vftable
+                        && merged
+                            .fields
+                            .iter()
+                            .any(|existing| existing.is_vftable && existing.name == field.name);
+                    if !same_offset || !(same_type || same_vftable) {
+                        return Err(IDAError::validation(format!(
+                            "Field '{}' conflicts with the existing member of {}",
+                            field.name, merged.name
+                        )));
+                    }
+                }
+                None => {
+                    if field.offset.is_none() && !merged.is_union {
+                        // Fields added after bitfields start past their storage
+                        for bitfield in &bitfields {
+                            if bitfield.fields_before.is_some_and(|before| before <= index) {
+                                next_offset = next_offset.max(bitfield.storage_end());
+                            }
+                        }
+                        let (size, align) = match field.field_type.layout() {
+                            // Flexible array members take no space
+                            Some((_, align)) if field.is_flexible => (0, align),
+                            Some(layout) => layout,
+                            None => {
+                                return Err(IDAError::validation(format!(
+                                    "Field '{}' has no known size, so it needs an explicit offset to be merged into {}",
+                                    field.name, merged.name
+                                )));
+                            }
+                        };
+                        let align = merged.pack.map_or(align, |pack| align.min(pack as u64));
+                        let offset = next_offset.next_multiple_of(align.max(1));
+                        next_offset = offset + size;
+                        field.offset = Some(offset);
+                    }
+                    let bits = field
+                        .offset
+                        .filter(|_| !merged.is_union)
+                        .zip(field.field_type.layout())
+                        .map(|(offset, (size, _))| (offset * 8, size * 8));
+                    if let Some(member) = bits.and_then(|bits| overlapped_member(&existing, bits)) {
+                        return Err(IDAError::validation(format!(
+                            "Field '{}' overlaps the existing member '{}' of {}",
+                            field.name, member.name, merged.name
+                        )));
+                    }
+                    merged.fields.push(field);
+                }
+            }
+        }
+
+        for mut bitfield in bitfields {
+            let bits = (bitfield.bit_offset as u64, bitfield.bit_width as u64);
+            match existing.iter().find(|member| member.name == bitfield.name) {
+                Some(member) => {
+                    if member.member_type.is_some() || member.bits != bits {
+                        return Err(IDAError::validation(format!(
+                            "Bitfield '{}' conflicts with the existing member of {}",
+                            bitfield.name, merged.name
+                        )));
+                    }
+                }
+                None => {
+                    if let Some(member) = overlapped_member(&existing, bits) {
+                        return Err(IDAError::validation(format!(
+                            "Bitfield '{}' overlaps the existing member '{}' of {}",
+                            bitfield.name, member.name, merged.name
+                        )));
+                    }
+                    // Every field of the merged type has an offset, so none
+                    // is placed after the bitfield
+                    bitfield.fields_before = None;
+                    merged.bitfields.push(bitfield);
+                }
+            }
+        }
+
+        // The checks done on the builder before merging did not see the
+        // existing members
+        merged.validate_members()?;
+        Ok(merged)
+    }
+
+    /// Create the struct/union without any members, or empty the type it
+    /// replaces
+    fn create_empty(&self) -> Result<u32, IDAError> {
+        ensure_writable()?;
+        if let Some(existing) = self.existing_ordinal() {
+            if !reset_to_empty_udt(existing, self.is_union) {
+                return Err(ffi_error(format!(
+                    "Failed to replace the existing type '{}'",
+                    self.name
+                )));
+            }
+            return Ok(existing);
+        }
+
         let struct_ordinal = if self.is_union {
             create_union_type(&self.name)
         } else {
@@ -281,67 +1962,252 @@ impl TypeBuilder for StructBuilder {
         };
 
         if struct_ordinal == 0 {
-            return Err(IDAError::ffi_with(format!(
+            return Err(ffi_error(format!(
                 "Failed to create {} '{}'",
                 if self.is_union { "union" } else { "struct" },
+                self.display_name()
+            )));
+        }
+
+        Ok(struct_ordinal)
+    }
+
+    /// Get the ordinal for this type in `registry`: the placeholder created
+    /// by an earlier forward reference, or else a newly created empty type
+    fn create_registered(&self, registry: &mut TypeRegistry) -> Result<u32, IDAError> {
+        let struct_ordinal = match registry.lookup(&self.name) {
+            Some(placeholder) => {
+                if !set_udt_kind(placeholder.ordinal(), self.is_union) {
+                    return Err(IDAError::already_exists(format!(
+                        "'{}' is already defined in the registry",
+                        self.name
+                    )));
+                }
+                placeholder.ordinal()
+            }
+            None => {
+                if let Some(existing) = self.existing_ordinal() {
+                    registry
+                        .replaced
+                        .entry(self.name.clone())
+                        .or_insert_with(|| save_type_definition(existing));
+                }
+                self.create_empty()?
+            }
+        };
+        registry.register(self.name.clone(), Type::from_ordinal(struct_ordinal));
+        registry.placeholders.remove(&self.name);
+        Ok(struct_ordinal)
+    }
+
+    /// Build the `<name>_vtbl` struct holding a pointer to each virtual method
+    fn build_vtable(&self) -> Result<Type, IDAError> {
+        let vtable = self.virtual_methods.iter().fold(
+            StructBuilder::new(format!("{}_vtbl", self.name))
+                .replace_existing(self.replace_existing),
+            |vtable, method| {
+                vtable.field(
+                    method.name.clone(),
+                    FieldType::Pointer(Box::new(FieldType::Existing(method.function_type.clone()))),
+                )
+            },
+        );
+        let vtable = vtable.build()?;
+        if !set_udt_vftable(vtable.ordinal(), true) {
+            return Err(ffi_error(format!(
+                "Failed to mark {}_vtbl as a vtable",
                 self.name
             )));
         }
+        Ok(vtable)
+    }
+
+    /// Add the members to the empty struct/union created for this builder,
+    /// resolving forward references through `registry` when given
+    fn populate(
+        mut self,
+        struct_ordinal: u32,
+        mut registry: Option<&mut TypeRegistry>,
+    ) -> Result<Type, IDAError> {
+        let type_name = self.display_name().to_owned();
+
+        let mut gap_names = self.gap_names();
+        if self.fill_gaps && !self.is_union {
+            self.insert_gap_fields(&mut gap_names)?;
+        }
+
+        // Set packing/alignment up front so it is kept as fields are added
+        if (self.pack.is_some() || self.align.is_some())
+            && !set_type_alignment(
+                struct_ordinal,
+                self.pack.unwrap_or(0),
+                self.align.unwrap_or(0),
+            )
+        {
+            return Err(ffi_error(format!(
+                "Failed to set alignment of {}",
+                type_name
+            )));
+        }
+
+        if self.no_tail_padding && !set_udt_unaligned(struct_ordinal, true) {
+            return Err(ffi_error(format!(
+                "Failed to turn off tail padding of {}",
+                type_name
+            )));
+        }
+
+        if self.is_class && !set_udt_cpp_object(struct_ordinal, true) {
+            return Err(ffi_error(format!(
+                "Failed to mark {} as a class",
+                type_name
+            )));
+        }
+
+        // Point `__vftable` at the vtable
+        if !self.virtual_methods.is_empty() {
+            let vtable = self.build_vtable()?;
+            if let Some(field) = self.fields.iter_mut().find(|field| field.is_vftable) {
+                field.field_type = FieldType::Pointer(Box::new(FieldType::Existing(vtable)));
+            }
+        }
 
         // Add fields
         let mut current_offset = 0u64;
-        for field in self.fields {
+        for (index, field) in self.fields.into_iter().enumerate() {
+            // Fields added after bitfields start past their storage, as in
+            // `field_extents`
+            for bitfield in &self.bitfields {
+                if !self.is_union && bitfield.fields_before.is_some_and(|before| before <= index) {
+                    current_offset = current_offset.max(bitfield.storage_end());
+                }
+            }
+
             // Get the field type ordinal
             let field_type_ordinal = match field.field_type {
-                FieldType::Primitive(prim) => {
-                    get_primitive_type_ordinal(prim.to_ida_type())
-                }
-                FieldType::Existing(typ) => typ.ordinal(),
                 FieldType::ForwardRef(ref name) => {
-                    // For forward references, we need to create a pointer to the struct being built
-                    // This allows self-referential structures like linked lists
+                    // Embedding by value needs the complete type
                     if name == &self.name {
-                        // Self-reference - create a pointer to this struct
-                        create_pointer_type(struct_ordinal)
-                    } else {
-                        // Forward reference to another type - this would need a type registry
-                        // For now, we'll return an error
-                        return Err(IDAError::ffi_with(format!(
-                            "Forward reference to '{}' not yet supported (only self-references allowed)",
-                            name
+                        return Err(IDAError::validation(format!(
+                            "Field '{}' embeds {} in itself, which would give it infinite size",
+                            field.name, type_name
                         )));
                     }
+                    match registry.as_deref() {
+                        Some(registry) => registry.resolve_embedded(name)?.ordinal(),
+                        None => {
+                            return Err(IDAError::not_found_with(format!(
+                                "Forward reference to '{}' cannot be resolved (build the types with a TypeRegistry)",
+                                name
+                            )));
+                        }
+                    }
                 }
+                FieldType::Pointer(target) => match *target {
+                    FieldType::ForwardRef(ref name) => {
+                        // Pointers may refer to types that are still being
+                        // built, which allows self-referential types
+                        if name == &self.name {
+                            create_pointer_type(struct_ordinal)
+                        } else if let Some(registry) = registry.as_deref_mut() {
+                            create_pointer_type(registry.resolve(name)?.ordinal())
+                        } else {
+                            return Err(IDAError::not_found_with(format!(
+                                "Forward reference to '{}' cannot be resolved (build the types with a TypeRegistry)",
+                                name
+                            )));
+                        }
+                    }
+                    target => FieldType::Pointer(Box::new(target)).into_ordinal("struct members")?,
+                },
+                array @ FieldType::Array(..) => match registry.as_deref_mut() {
+                    Some(registry) => array.resolved(registry)?.into_ordinal("struct members")?,
+                    None => array.into_ordinal("struct members")?,
+                },
+                other => other.into_ordinal("struct members")?,
             };
 
             if field_type_ordinal == 0 {
-                return Err(IDAError::ffi_with(format!(
+                return Err(ffi_error(format!(
                     "Invalid field type for field '{}'",
                     field.name
                 )));
             }
 
-            let offset = field.offset.unwrap_or(current_offset);
+            // Flexible array members are zero-length arrays of the element type
+            let field_type_ordinal = if field.is_flexible {
+                match create_array_type(field_type_ordinal, 0) {
+                    0 => {
+                        return Err(ffi_error(format!(
+                            "Failed to create flexible array type for field '{}'",
+                            field.name
+                        )));
+                    }
+                    array_ordinal => array_ordinal,
+                }
+            } else {
+                field_type_ordinal
+            };
+
+            // Opaque types report no size, so a member of one cannot be laid
+            // out; empty base classes and flexible arrays really take no space
+            let field_size = get_type_size(field_type_ordinal);
+            if field_size == 0 && !field.is_flexible && !field.is_baseclass {
+                return Err(IDAError::validation(format!(
+                    "Field '{}' of {} has a type of unknown size; only a pointer to it can be a member",
+                    field.name,
+                    type_name
+                )));
+            }
+            let field_align = match get_type_alignment(field_type_ordinal) {
+                0 => 1,
+                align => self.pack.map_or(align, |pack| align.min(pack as u64)),
+            };
+
+            let offset = field
+                .offset
+                .unwrap_or_else(|| current_offset.next_multiple_of(field_align));
             
-            let success = add_field_to_type(
-                struct_ordinal,
-                &field.name,
-                field_type_ordinal,
-                offset,
-            );
+            let success = if field.is_baseclass {
+                add_baseclass(struct_ordinal, &field.name, field_type_ordinal)
+            } else {
+                add_field_to_type(
+                    struct_ordinal,
+                    &field.name,
+                    field_type_ordinal,
+                    offset,
+                )
+            };
 
             if !success {
-                return Err(IDAError::ffi_with(format!(
+                return Err(ffi_error(format!(
                     "Failed to add field '{}' to {}",
                     field.name,
-                    self.name
+                    type_name
+                )));
+            }
+
+            if field.is_vftable && !set_udt_member_vftable(struct_ordinal, &field.name) {
+                return Err(ffi_error(format!(
+                    "Failed to mark '{}' as the vtable pointer of {}",
+                    field.name,
+                    type_name
+                )));
+            }
+
+            if let Some((comment, repeatable)) = &field.comment
+                && !set_field_comment(struct_ordinal, &field.name, comment, *repeatable)
+            {
+                return Err(ffi_error(format!(
+                    "Failed to set comment on field '{}' of {}",
+                    field.name,
+                    type_name
                 )));
             }
 
             // Update offset for next field (only for structs, not unions)
             if !self.is_union && field.offset.is_none() {
-                let field_size = get_type_size(field_type_ordinal);
-                current_offset += if field_size > 0 { field_size } else { 8 };
+                current_offset = offset + field_size;
             }
         }
 
@@ -353,26 +2219,92 @@ impl TypeBuilder for StructBuilder {
                 bitfield.bit_offset,
                 bitfield.bit_width,
                 bitfield.is_unsigned,
+                bitfield.storage_bytes.unwrap_or(0),
             );
 
             if !success {
-                return Err(IDAError::ffi_with(format!(
+                return Err(ffi_error(format!(
                     "Failed to add bitfield '{}' to {}",
                     bitfield.name,
-                    self.name
+                    type_name
+                )));
+            }
+        }
+
+        // Pad up to the requested size
+        if let Some(total_size) = self.total_size {
+            let current_size = get_type_size(struct_ordinal);
+            if current_size > total_size {
+                return Err(ffi_error(format!(
+                    "{} is {} bytes, larger than its requested size of {}",
+                    type_name, current_size, total_size
                 )));
             }
+            if current_size < total_size {
+                let (gap_offset, gap_len) = if self.is_union {
+                    (0, total_size)
+                } else {
+                    (current_size, total_size - current_size)
+                };
+                let gap_type = ArrayBuilder::new(PrimitiveType::UInt8, gap_len as u32).build()?;
+                let gap_name = gap_names.next().unwrap_or_default();
+                if !add_field_to_type(struct_ordinal, &gap_name, gap_type.ordinal(), gap_offset) {
+                    return Err(ffi_error(format!(
+                        "Failed to pad {} to {} bytes",
+                        type_name, total_size
+                    )));
+                }
+            }
         }
 
         // Finalize the type
         if !finalize_type(struct_ordinal) {
-            return Err(IDAError::ffi_with("Failed to finalize type"));
+            return Err(ffi_error("Failed to finalize type"));
         }
 
         Ok(Type::from_ordinal(struct_ordinal))
     }
 }
 
+impl TypeValidator for StructBuilder {
+    fn validate(&self) -> Result<(), IDAError> {
+        // Check for empty name
+        if self.name.is_empty() {
+            return Err(IDAError::validation("Struct/union name cannot be empty"));
+        }
+        
+        self.validate_members()
+    }
+}
+
+impl TypeBuilder for StructBuilder {
+    fn build(self) -> Result<Type, IDAError> {
+        // Validate before building
+        TypeValidator::validate(&self)?;
+        self.merged_with_existing()?.create()
+    }
+
+    fn build_boxed(self: Box<Self>, registry: &mut TypeRegistry) -> Result<Type, IDAError> {
+        self.build_into(registry)
+    }
+
+    fn defined_name(&self) -> Option<&str> {
+        Some(&self.name)
+    }
+
+    fn embedded_refs(&self) -> Vec<&str> {
+        self.fields
+            .iter()
+            .filter_map(|field| field.field_type.embedded_ref())
+            .collect()
+    }
+
+    fn check(&self) -> Result<(), IDAError> {
+        TypeValidator::validate(self)?;
+        self.check_members()
+    }
+}
+
 // Implement From traits for convenient field type creation
 impl From<PrimitiveType> for FieldType {
     fn from(prim: PrimitiveType) -> Self {
@@ -387,31 +2319,6 @@ impl From<Type> for FieldType {
 }
 
 
-// Clone implementation for StructBuilder
-impl Clone for StructBuilder {
-    fn clone(&self) -> Self {
-        Self {
-            name: self.name.clone(),
-            fields: self.fields.iter().map(|f| StructField {
-                name: f.name.clone(),
-                field_type: match &f.field_type {
-                    FieldType::Primitive(p) => FieldType::Primitive(*p),
-                    FieldType::Existing(t) => FieldType::Existing(t.clone()),
-                    FieldType::ForwardRef(s) => FieldType::ForwardRef(s.clone()),
-                },
-                offset: f.offset,
-            }).collect(),
-            bitfields: self.bitfields.iter().map(|b| BitfieldInfo {
-                name: b.name.clone(),
-                bit_offset: b.bit_offset,
-                bit_width: b.bit_width,
-                is_unsigned: b.is_unsigned,
-            }).collect(),
-            is_union: self.is_union,
-        }
-    }
-}
-
 // We need to implement Clone for Type
 impl Clone for Type {
     fn clone(&self) -> Self {
@@ -419,15 +2326,73 @@ impl Clone for Type {
     }
 }
 
+impl Type {
+    /// Start building an array of `num_elements` of this type
+    pub fn array(&self, num_elements: u32) -> ArrayBuilder {
+        FieldType::from(self.clone()).array(num_elements)
+    }
+
+    /// Start building a pointer to this type
+    ///
+    /// `my_struct.ptr().build()?` builds `MyStruct*`.
+    pub fn ptr(&self) -> PointerBuilder {
+        FieldType::from(self.clone()).ptr()
+    }
+
+    /// Start building a pointer to a pointer to this type
+    pub fn ptr_ptr(&self) -> PointerBuilder {
+        FieldType::from(self.clone()).ptr_ptr()
+    }
+}
+
 /// Builder for creating enum types
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct EnumBuilder {
     name: String,
     width: u32,
+    #[cfg_attr(feature = "serde", serde(default))]
     members: Vec<EnumMember>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    is_bitmask: bool,
+    #[cfg_attr(feature = "serde", serde(default))]
+    base_type: Option<FieldType>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    is_signed: Option<bool>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    representation: Option<EnumRepr>,
+    /// Value of the next `auto_member`, if set by `auto_start`
+    #[cfg_attr(feature = "serde", serde(default))]
+    auto_start: Option<i64>,
+    #[cfg_attr(feature = "serde", serde(default = "EnumBuilder::default_auto_step"))]
+    auto_step: i64,
+}
+
+/// How enum member values are displayed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum EnumRepr {
+    Hex,
+    Dec,
+    /// Character constants, like `'A'`
+    Char,
+    Bin,
+}
+
+impl EnumRepr {
+    /// Radix passed to the bridge; 0 selects character constants
+    fn radix(self) -> u32 {
+        match self {
+            EnumRepr::Hex => 16,
+            EnumRepr::Dec => 10,
+            EnumRepr::Char => 0,
+            EnumRepr::Bin => 2,
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 struct EnumMember {
     name: String,
     value: i64,
@@ -440,9 +2405,75 @@ impl EnumBuilder {
             name: name.into(),
             width,
             members: Vec::new(),
+            is_bitmask: false,
+            base_type: None,
+            is_signed: None,
+            representation: None,
+            auto_start: None,
+            auto_step: 1,
         }
     }
 
+    /// Step of `auto_member` values unless set by `auto_step`
+    #[cfg(feature = "serde")]
+    fn default_auto_step() -> i64 {
+        1
+    }
+
+    /// Set how member values are displayed in the disassembly
+    pub fn representation(mut self, representation: EnumRepr) -> Self {
+        self.representation = Some(representation);
+        self
+    }
+
+    /// Declare the underlying type, like `enum X : int16_t`
+    ///
+    /// Its size must match the enum width. Unless `signed` is set, the enum
+    /// is signed if the underlying type is a signed primitive.
+    pub fn base_type(mut self, base_type: impl Into<FieldType>) -> Self {
+        self.base_type = Some(base_type.into());
+        self
+    }
+
+    /// Set whether member values are signed
+    pub fn signed(mut self, is_signed: bool) -> Self {
+        self.is_signed = Some(is_signed);
+        self
+    }
+
+    /// Get the value of the member named `name`
+    pub fn member_value(&self, name: &str) -> Option<i64> {
+        self.members
+            .iter()
+            .find(|member| member.name == name)
+            .map(|member| member.value)
+    }
+
+    /// Get the name of the first member with the given value
+    pub fn member_name(&self, value: i64) -> Option<&str> {
+        self.members
+            .iter()
+            .find(|member| member.value == value)
+            .map(|member| member.name.as_str())
+    }
+
+    /// Whether member values are signed, from `signed` or the base type
+    fn is_signed(&self) -> bool {
+        self.is_signed.unwrap_or(match &self.base_type {
+            Some(FieldType::Primitive(prim)) => prim.is_signed(),
+            _ => false,
+        })
+    }
+
+    /// Make this a bitmask enum, whose members are flags that can be OR-ed
+    /// together (displayed as `A|B`)
+    ///
+    /// Every member value must then be a distinct power of two.
+    pub fn bitmask(mut self, is_bitmask: bool) -> Self {
+        self.is_bitmask = is_bitmask;
+        self
+    }
+
     /// Add a member to the enum with an explicit value
     pub fn member(mut self, name: impl Into<String>, value: i64) -> Self {
         self.members.push(EnumMember {
@@ -452,10 +2483,34 @@ impl EnumBuilder {
         self
     }
 
+    /// Set the value of the next `auto_member`
+    pub fn auto_start(mut self, value: i64) -> Self {
+        self.auto_start = Some(value);
+        self
+    }
+
+    /// Set the increment used by subsequent `auto_member` calls (default 1)
+    pub fn auto_step(mut self, step: i64) -> Self {
+        self.auto_step = step;
+        self
+    }
+
     /// Add a member with auto-incremented value
+    ///
+    /// The value follows the last member, whether it was added explicitly or
+    /// not, unless `auto_start` was set since. Members of an enum already
+    /// made a `bitmask` take successive single bits instead, starting at 1:
+    /// the value is the bit above the highest bit of the last member, and
+    /// `auto_step` is ignored.
     pub fn auto_member(mut self, name: impl Into<String>) -> Self {
-        let next_value = if let Some(last) = self.members.last() {
-            last.value + 1
+        let next_value = if let Some(start) = self.auto_start.take() {
+            start
+        } else if self.is_bitmask {
+            // Past the top bit this is 0, which validation rejects
+            let last = self.members.last().map_or(0, |last| last.value);
+            1i64.checked_shl(64 - last.leading_zeros()).unwrap_or(0)
+        } else if let Some(last) = self.members.last() {
+            last.value + self.auto_step
         } else {
             0
         };
@@ -465,34 +2520,112 @@ impl EnumBuilder {
         });
         self
     }
+
+    /// Get the name of the enum being built
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Build the enum and register it in `registry`, so that struct/union
+    /// builders can refer to it through `FieldType::ForwardRef`
+    pub fn build_into(self, registry: &mut TypeRegistry) -> Result<Type, IDAError> {
+        let name = self.name.clone();
+        let typ = self.build()?;
+        registry.register(name, typ.clone());
+        Ok(typ)
+    }
 }
 
 impl TypeValidator for EnumBuilder {
     fn validate(&self) -> Result<(), IDAError> {
         // Check for empty name
         if self.name.is_empty() {
-            return Err(IDAError::ffi_with("Enum name cannot be empty"));
+            return Err(IDAError::validation("Enum name cannot be empty"));
         }
         
         // Validate width
         if ![1, 2, 4, 8].contains(&self.width) {
-            return Err(IDAError::ffi_with(format!(
+            return Err(IDAError::validation(format!(
                 "Invalid enum width {}. Must be 1, 2, 4, or 8",
                 self.width
             )));
         }
         
+        // The underlying type must have the enum's width
+        if let Some(base_type) = &self.base_type {
+            match base_type.layout() {
+                Some((size, _)) if size == self.width as u64 => {}
+                Some((size, _)) => {
+                    return Err(IDAError::validation(format!(
+                        "Base type of enum {} is {} bytes, but the enum width is {}",
+                        self.name, size, self.width
+                    )));
+                }
+                None => {
+                    return Err(IDAError::validation(format!(
+                        "Base type of enum {} has no known size",
+                        self.name
+                    )));
+                }
+            }
+        }
+        
         // Check for duplicate member names
         let mut member_names = std::collections::HashSet::new();
         for member in &self.members {
             if !member_names.insert(&member.name) {
-                return Err(IDAError::ffi_with(format!(
+                return Err(IDAError::validation(format!(
                     "Duplicate enum member name '{}' in {}",
                     member.name, self.name
                 )));
             }
         }
         
+        // Member values must fit in the enum's width
+        if self.width < 8 {
+            let bits = self.width * 8;
+            let (min, max) = if self.is_signed() {
+                (-(1i64 << (bits - 1)), (1i64 << (bits - 1)) - 1)
+            } else {
+                (0, (1i64 << bits) - 1)
+            };
+            for member in &self.members {
+                if member.value < min || member.value > max {
+                    return Err(IDAError::validation(format!(
+                        "Value {} of member '{}' does not fit in {}-byte {} enum {} ({}..={})",
+                        member.value,
+                        member.name,
+                        self.width,
+                        if self.is_signed() { "signed" } else { "unsigned" },
+                        self.name,
+                        min,
+                        max
+                    )));
+                }
+            }
+        }
+        
+        // Flags must each occupy their own bit
+        if self.is_bitmask {
+            let mut used_bits = 0u64;
+            for member in &self.members {
+                let bit = member.value as u64;
+                if !bit.is_power_of_two() {
+                    return Err(IDAError::validation(format!(
+                        "Member '{}' of bitmask enum {} has value {:#x}, which is not a power of two",
+                        member.name, self.name, member.value
+                    )));
+                }
+                if used_bits & bit != 0 {
+                    return Err(IDAError::validation(format!(
+                        "Member '{}' of bitmask enum {} reuses bit {:#x}",
+                        member.name, self.name, bit
+                    )));
+                }
+                used_bits |= bit;
+            }
+        }
+        
         Ok(())
     }
 }
@@ -502,19 +2635,66 @@ impl TypeBuilder for EnumBuilder {
         // Validate before building
         TypeValidator::validate(&self)?;
 
+        ensure_writable()?;
+
         // Create the enum
         let enum_ordinal = create_enum_type(&self.name, self.width);
         if enum_ordinal == 0 {
-            return Err(IDAError::ffi_with(format!(
+            return Err(ffi_error(format!(
                 "Failed to create enum '{}'",
                 self.name
             )));
         }
 
+        // Don't leave a partially built enum behind
+        self.populate(enum_ordinal).inspect_err(|_| {
+            delete_type(enum_ordinal);
+        })
+    }
+
+    fn build_boxed(self: Box<Self>, registry: &mut TypeRegistry) -> Result<Type, IDAError> {
+        self.build_into(registry)
+    }
+
+    fn defined_name(&self) -> Option<&str> {
+        Some(&self.name)
+    }
+
+    fn check(&self) -> Result<(), IDAError> {
+        TypeValidator::validate(self)
+    }
+}
+
+impl EnumBuilder {
+    /// Set up the empty enum created for this builder and add its members
+    fn populate(self, enum_ordinal: u32) -> Result<Type, IDAError> {
+        if self.is_bitmask && !set_enum_bitmask(enum_ordinal, true) {
+            return Err(ffi_error(format!(
+                "Failed to make enum '{}' a bitmask",
+                self.name
+            )));
+        }
+
+        if self.is_signed() && !set_enum_signed(enum_ordinal, true) {
+            return Err(ffi_error(format!(
+                "Failed to make enum '{}' signed",
+                self.name
+            )));
+        }
+
+        if let Some(representation) = self.representation
+            && !set_enum_radix(enum_ordinal, representation.radix(), self.is_signed())
+        {
+            return Err(ffi_error(format!(
+                "Failed to set the representation of enum '{}'",
+                self.name
+            )));
+        }
+
         // Add members
         for member in self.members {
             if !add_enum_member(enum_ordinal, &member.name, member.value) {
-                return Err(IDAError::ffi_with(format!(
+                return Err(ffi_error(format!(
                     "Failed to add member '{}' to enum '{}'",
                     member.name, self.name
                 )));
@@ -523,7 +2703,7 @@ impl TypeBuilder for EnumBuilder {
 
         // Finalize the type
         if !finalize_type(enum_ordinal) {
-            return Err(IDAError::ffi_with("Failed to finalize enum type"));
+            return Err(ffi_error("Failed to finalize enum type"));
         }
 
         Ok(Type::from_ordinal(enum_ordinal))
@@ -532,9 +2712,14 @@ impl TypeBuilder for EnumBuilder {
 
 /// Builder for creating array types
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ArrayBuilder {
     element_type: FieldType,
-    num_elements: u32,
+    /// Element counts, outermost dimension first
+    dimensions: Vec<u32>,
+    /// Whether the outermost dimension may be zero, as in `int a[]`
+    #[cfg_attr(feature = "serde", serde(default))]
+    flexible: bool,
 }
 
 impl ArrayBuilder {
@@ -542,98 +2727,282 @@ impl ArrayBuilder {
     pub fn new(element_type: impl Into<FieldType>, num_elements: u32) -> Self {
         Self {
             element_type: element_type.into(),
-            num_elements,
+            dimensions: vec![num_elements],
+            flexible: false,
+        }
+    }
+
+    /// Set the dimensions of a multi-dimensional array, outermost first
+    ///
+    /// `dimensions(&[4, 8])` on an `int` element builds `int[4][8]`.
+    pub fn dimensions(mut self, dimensions: &[u32]) -> Self {
+        self.dimensions = dimensions.to_vec();
+        self
+    }
+
+    /// Allow zero elements in the outermost dimension, for a flexible array
+    /// like `int a[]` or `int a[][8]`
+    ///
+    /// Zero-length arrays have no size, so they are rejected unless this is
+    /// set. Use `StructBuilder::flexible_array_field` to end a struct with one.
+    pub fn flexible(mut self, is_flexible: bool) -> Self {
+        self.flexible = is_flexible;
+        self
+    }
+}
+
+impl TypeValidator for ArrayBuilder {
+    fn validate(&self) -> Result<(), IDAError> {
+        if self.dimensions.is_empty() {
+            return Err(IDAError::validation("Array must have at least one dimension"));
+        }
+
+        if self.dimensions[0] == 0 && !self.flexible {
+            return Err(IDAError::validation(
+                "Array cannot have zero elements unless it is marked flexible",
+            ));
         }
+
+        // Only the outermost dimension may be left open, as in `int a[][8]`
+        if let Some(position) = self.dimensions.iter().skip(1).position(|&n| n == 0) {
+            return Err(IDAError::validation(format!(
+                "Array dimension {} cannot be zero",
+                position + 1
+            )));
+        }
+
+        Ok(())
     }
 }
 
 impl TypeBuilder for ArrayBuilder {
     fn build(self) -> Result<Type, IDAError> {
-        // Get the element type ordinal
-        let element_ordinal = match self.element_type {
-            FieldType::Primitive(prim) => get_primitive_type_ordinal(prim.to_ida_type()),
-            FieldType::Existing(typ) => typ.ordinal(),
-            FieldType::ForwardRef(_) => {
-                return Err(IDAError::ffi_with(
-                    "Forward references not supported in array element types"
-                ));
+        // Validate before building
+        TypeValidator::validate(&self)?;
+
+        ensure_writable()?;
+
+        // Don't leave the types made along the way behind on failure
+        deleting_created_on_error(|| {
+            // Get the element type ordinal
+            let element_ordinal = self.element_type.into_ordinal("array element types")?;
+
+            if element_ordinal == 0 {
+                return Err(ffi_error("Invalid element type for array"));
             }
-        };
 
-        if element_ordinal == 0 {
-            return Err(IDAError::ffi_with("Invalid element type for array"));
-        }
+            // Create the array types from the innermost dimension outwards
+            let mut array_ordinal = element_ordinal;
+            for &num_elements in self.dimensions.iter().rev() {
+                array_ordinal = create_array_type(array_ordinal, num_elements);
+                if array_ordinal == 0 {
+                    return Err(ffi_error("Failed to create array type"));
+                }
+            }
+
+            Ok(Type::from_ordinal(array_ordinal))
+        })
+    }
+
+    fn build_boxed(mut self: Box<Self>, registry: &mut TypeRegistry) -> Result<Type, IDAError> {
+        self.element_type = self.element_type.resolved(registry)?;
+        self.build()
+    }
 
-        // Create the array type
-        let array_ordinal = create_array_type(element_ordinal, self.num_elements);
-        if array_ordinal == 0 {
-            return Err(IDAError::ffi_with("Failed to create array type"));
-        }
+    fn embedded_refs(&self) -> Vec<&str> {
+        self.element_type.embedded_ref().into_iter().collect()
+    }
 
-        Ok(Type::from_ordinal(array_ordinal))
+    fn check(&self) -> Result<(), IDAError> {
+        TypeValidator::validate(self)?;
+        self.element_type.check_resolvable("array element types")
     }
 }
 
 /// Builder for creating pointer types
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct PointerBuilder {
     target_type: FieldType,
+    #[cfg_attr(feature = "serde", serde(default))]
+    const_target: bool,
+    #[cfg_attr(feature = "serde", serde(default))]
+    volatile_target: bool,
+    #[cfg_attr(feature = "serde", serde(default))]
+    size_bytes: Option<u32>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    model: PointerModel,
+}
+
+/// Memory model of a pointer on segmented architectures
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+enum PointerModel {
+    #[default]
+    Default,
+    Near,
+    Far,
 }
 
 impl PointerBuilder {
-    /// Create a new pointer builder
+    /// Create a new pointer builder (a `void` target yields `void*`)
     pub fn new(target_type: impl Into<FieldType>) -> Self {
         Self {
             target_type: target_type.into(),
+            const_target: false,
+            volatile_target: false,
+            size_bytes: None,
+            model: PointerModel::Default,
+        }
+    }
+
+    /// Set the pointer size (2, 4 or 8 bytes), e.g. 4 for a `__ptr32`
+    /// pointer in a 64-bit database
+    pub fn size_bytes(mut self, size_bytes: u32) -> Self {
+        self.size_bytes = Some(size_bytes);
+        self
+    }
+
+    /// Make this a near pointer
+    pub fn near(mut self) -> Self {
+        self.model = PointerModel::Near;
+        self
+    }
+
+    /// Make this a far pointer
+    pub fn far(mut self) -> Self {
+        self.model = PointerModel::Far;
+        self
+    }
+
+    /// Point to a `const` target, like `const char*`
+    pub fn const_target(mut self, is_const: bool) -> Self {
+        self.const_target = is_const;
+        self
+    }
+
+    /// Point to a `volatile` target, like `volatile uint32_t*`
+    pub fn volatile_target(mut self, is_volatile: bool) -> Self {
+        self.volatile_target = is_volatile;
+        self
+    }
+}
+
+impl TypeValidator for PointerBuilder {
+    fn validate(&self) -> Result<(), IDAError> {
+        if let Some(size_bytes) = self.size_bytes {
+            if ![2, 4, 8].contains(&size_bytes) {
+                return Err(IDAError::validation(format!(
+                    "Invalid pointer size {}. Must be 2, 4, or 8",
+                    size_bytes
+                )));
+            }
+            if self.model != PointerModel::Default {
+                return Err(IDAError::validation(
+                    "Pointer size cannot be combined with a near/far model",
+                ));
+            }
         }
+        Ok(())
     }
 }
 
 impl TypeBuilder for PointerBuilder {
     fn build(self) -> Result<Type, IDAError> {
-        // Get the target type ordinal
-        let target_ordinal = match self.target_type {
-            FieldType::Primitive(prim) => get_primitive_type_ordinal(prim.to_ida_type()),
-            FieldType::Existing(typ) => typ.ordinal(),
-            FieldType::ForwardRef(_) => {
-                return Err(IDAError::ffi_with(
-                    "Forward references not supported in pointer target types"
-                ));
+        // Validate before building
+        TypeValidator::validate(&self)?;
+
+        ensure_writable()?;
+
+        // Don't leave the types made along the way behind on failure
+        deleting_created_on_error(|| {
+            // Get the target type ordinal
+            let target_ordinal = self.target_type.into_ordinal("pointer target types")?;
+
+            if target_ordinal == 0 {
+                return Err(ffi_error("Invalid target type for pointer"));
             }
-        };
 
-        if target_ordinal == 0 {
-            return Err(IDAError::ffi_with("Invalid target type for pointer"));
-        }
+            // Qualify the target before pointing at it
+            let target_ordinal = if self.const_target || self.volatile_target {
+                match create_qualified_type(target_ordinal, self.const_target, self.volatile_target)
+                {
+                    0 => return Err(ffi_error("Failed to qualify pointer target type")),
+                    qualified => qualified,
+                }
+            } else {
+                target_ordinal
+            };
 
-        // Create the pointer type
-        let pointer_ordinal = create_pointer_type(target_ordinal);
-        if pointer_ordinal == 0 {
-            return Err(IDAError::ffi_with("Failed to create pointer type"));
-        }
+            // Create the pointer type
+            let model = match self.model {
+                PointerModel::Default => 0,
+                PointerModel::Near => 1,
+                PointerModel::Far => 2,
+            };
+            let pointer_ordinal = if self.size_bytes.is_some() || model != 0 {
+                create_sized_pointer_type(target_ordinal, self.size_bytes.unwrap_or(0), model)
+            } else {
+                create_pointer_type(target_ordinal)
+            };
+            if pointer_ordinal == 0 {
+                return Err(ffi_error("Failed to create pointer type"));
+            }
+
+            Ok(Type::from_ordinal(pointer_ordinal))
+        })
+    }
+
+    fn build_boxed(mut self: Box<Self>, registry: &mut TypeRegistry) -> Result<Type, IDAError> {
+        // Pointers may refer to types that are not built yet
+        self.target_type = match self.target_type {
+            FieldType::ForwardRef(name) => FieldType::Existing(registry.resolve(&name)?),
+            target_type => target_type.resolved(registry)?,
+        };
+        self.build()
+    }
 
-        Ok(Type::from_ordinal(pointer_ordinal))
+    fn check(&self) -> Result<(), IDAError> {
+        TypeValidator::validate(self)?;
+        self.target_type.check_resolvable("pointer target types")
     }
 }
 
 /// Builder for creating function types
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct FunctionBuilder {
+    #[cfg_attr(feature = "serde", serde(default))]
     return_type: Option<FieldType>,
+    #[cfg_attr(feature = "serde", serde(default))]
     parameters: Vec<FunctionParameter>,
     calling_convention: CallingConvention,
+    #[cfg_attr(feature = "serde", serde(default))]
     is_vararg: bool,
+    #[cfg_attr(feature = "serde", serde(default))]
     attributes: FunctionAttributes,
+    #[cfg_attr(feature = "serde", serde(default))]
+    purged_bytes: Option<u32>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    spoiled: Vec<String>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    return_register: Option<(FieldType, String)>,
 }
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 struct FunctionParameter {
     name: String,
     param_type: FieldType,
+    #[cfg_attr(feature = "serde", serde(default))]
     is_hidden: bool,
+    #[cfg_attr(feature = "serde", serde(default))]
+    register: Option<String>,
 }
 
 #[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
 struct FunctionAttributes {
     is_noreturn: bool,
     is_pure: bool,
@@ -646,20 +3015,35 @@ struct FunctionAttributes {
 
 /// Calling conventions
 #[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum CallingConvention {
     Unknown,
     Cdecl,
     Stdcall,
     Pascal,
+    /// `__fastcall`; for a member function, `this` is the first register
+    /// argument (`ecx` on x86), so add it with `hidden_param` before the others
     Fastcall,
     Thiscall,
     Swift,
     Golang,
+    /// `__usercall`: arguments and return value in explicit locations
+    Special,
+    /// `__usercall` with an ellipsis
+    SpecialEllipsis,
+    /// `__userpurge`: explicit locations, callee purges the stack
+    SpecialPurge,
+    /// MSVC `__vectorcall`; IDA has no dedicated `CM_CC_*` value for it, so it is
+    /// encoded as `__usercall` and argument locations must be given explicitly
+    Vectorcall,
+    /// Intel `__regcall`; encoded as `__usercall` like `Vectorcall`
+    Regcall,
     Custom(u32),
 }
 
 impl CallingConvention {
-    fn to_ida_cc(self) -> u32 {
+    /// Get the IDA calling convention code (`CM_CC_*` value)
+    pub fn to_ida_cc(self) -> u32 {
         match self {
             CallingConvention::Unknown => 0x10,   // CM_CC_UNKNOWN
             CallingConvention::Cdecl => 0x30,     // CM_CC_CDECL
@@ -669,9 +3053,48 @@ impl CallingConvention {
             CallingConvention::Thiscall => 0x80,  // CM_CC_THISCALL
             CallingConvention::Swift => 0x90,     // CM_CC_SWIFT
             CallingConvention::Golang => 0xB0,    // CM_CC_GOLANG
+            CallingConvention::SpecialEllipsis => 0xD0, // CM_CC_SPECIALE
+            CallingConvention::SpecialPurge => 0xE0, // CM_CC_SPECIALP
+            CallingConvention::Special => 0xF0,   // CM_CC_SPECIAL
+            CallingConvention::Vectorcall => 0xF0, // CM_CC_SPECIAL (no native code)
+            CallingConvention::Regcall => 0xF0,   // CM_CC_SPECIAL (no native code)
             CallingConvention::Custom(cc) => cc,
         }
     }
+
+    /// Get the calling convention for an IDA `CM_CC_*` code
+    ///
+    /// Only the convention bits (`CM_CC_MASK`) are inspected; codes that don't
+    /// correspond to a named variant are returned as `Custom(code)`. Both
+    /// `Vectorcall` and `Regcall` decode as `Special`.
+    pub fn from_ida_cc(code: u32) -> CallingConvention {
+        match code & 0xF0 {
+            0x10 => CallingConvention::Unknown,
+            0x30 => CallingConvention::Cdecl,
+            0x50 => CallingConvention::Stdcall,
+            0x60 => CallingConvention::Pascal,
+            0x70 => CallingConvention::Fastcall,
+            0x80 => CallingConvention::Thiscall,
+            0x90 => CallingConvention::Swift,
+            0xB0 => CallingConvention::Golang,
+            0xD0 => CallingConvention::SpecialEllipsis,
+            0xE0 => CallingConvention::SpecialPurge,
+            0xF0 => CallingConvention::Special,
+            _ => CallingConvention::Custom(code),
+        }
+    }
+
+    /// Check if this convention uses explicit argument locations
+    pub fn is_special(self) -> bool {
+        matches!(
+            self,
+            CallingConvention::Special
+                | CallingConvention::SpecialEllipsis
+                | CallingConvention::SpecialPurge
+                | CallingConvention::Vectorcall
+                | CallingConvention::Regcall
+        )
+    }
 }
 
 impl FunctionBuilder {
@@ -683,31 +3106,74 @@ impl FunctionBuilder {
             calling_convention: CallingConvention::Unknown,
             is_vararg: false,
             attributes: FunctionAttributes::default(),
+            purged_bytes: None,
+            spoiled: Vec::new(),
+            return_register: None,
         }
     }
 
-    /// Set the return type
+    /// Set the return type (`void` is the same as not setting one)
     pub fn returns(mut self, return_type: impl Into<FieldType>) -> Self {
         self.return_type = Some(return_type.into());
         self
     }
 
+    /// Set the return type and the register it is returned in (e.g. `"rdx"`)
+    ///
+    /// Like `param_in_register`, this forces a special calling convention.
+    /// It cannot be combined with `returns`.
+    pub fn returns_in_register(
+        mut self,
+        return_type: impl Into<FieldType>,
+        reg_name: impl Into<String>,
+    ) -> Self {
+        self.return_register = Some((return_type.into(), reg_name.into()));
+        self
+    }
+
     /// Add a parameter
     pub fn param(mut self, name: impl Into<String>, param_type: impl Into<FieldType>) -> Self {
         self.parameters.push(FunctionParameter {
             name: name.into(),
             param_type: param_type.into(),
             is_hidden: false,
+            register: None,
         });
         self
     }
 
+    /// Add a parameter without a name (as in a forward declaration)
+    pub fn unnamed_param(self, param_type: impl Into<FieldType>) -> Self {
+        self.param(String::new(), param_type)
+    }
+
     /// Add a hidden parameter (like 'this' pointer)
     pub fn hidden_param(mut self, name: impl Into<String>, param_type: impl Into<FieldType>) -> Self {
         self.parameters.push(FunctionParameter {
             name: name.into(),
             param_type: param_type.into(),
             is_hidden: true,
+            register: None,
+        });
+        self
+    }
+
+    /// Add a parameter passed in a specific register (e.g. `"rdi"`)
+    ///
+    /// Register-assigned parameters require explicit argument locations, so
+    /// unless one of the special conventions was chosen the function is built
+    /// with `CallingConvention::Special` (`__usercall`).
+    pub fn param_in_register(
+        mut self,
+        name: impl Into<String>,
+        param_type: impl Into<FieldType>,
+        reg_name: impl Into<String>,
+    ) -> Self {
+        self.parameters.push(FunctionParameter {
+            name: name.into(),
+            param_type: param_type.into(),
+            is_hidden: false,
+            register: Some(reg_name.into()),
         });
         self
     }
@@ -724,6 +3190,25 @@ impl FunctionBuilder {
         self
     }
 
+    /// Set the number of stack bytes the callee purges on return
+    /// (stdcall-style conventions)
+    ///
+    /// IDA derives the purge from the stack arguments, so `build` fails if they
+    /// don't add up to `purged`. A convention that doesn't purge, like the
+    /// default `Unknown`, is turned into `__userpurge`, except for `Cdecl`:
+    /// the caller cleans the stack there, so IDA ignores the purge and the
+    /// function stays cdecl.
+    pub fn purged_bytes(mut self, purged: u32) -> Self {
+        self.purged_bytes = Some(purged);
+        self
+    }
+
+    /// Declare registers the function spoils (clobbers), e.g. `["rax", "rcx"]`
+    pub fn spoils(mut self, reg_names: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        self.spoiled.extend(reg_names.into_iter().map(Into::into));
+        self
+    }
+
     /// Mark function as noreturn
     pub fn noreturn(mut self) -> Self {
         self.attributes.is_noreturn = true;
@@ -773,124 +3258,549 @@ impl TypeValidator for FunctionBuilder {
         let mut param_names = std::collections::HashSet::new();
         for param in &self.parameters {
             if !param.name.is_empty() && !param_names.insert(&param.name) {
-                return Err(IDAError::ffi_with(format!(
+                return Err(IDAError::validation(format!(
                     "Duplicate parameter name '{}'",
                     param.name
                 )));
             }
         }
         
+        // Hidden parameters (like 'this') must precede all visible ones
+        let mut seen_visible = false;
+        for param in &self.parameters {
+            if !param.is_hidden {
+                seen_visible = true;
+            } else if seen_visible {
+                return Err(IDAError::validation(format!(
+                    "Hidden parameter '{}' must come before all visible parameters",
+                    param.name
+                )));
+            }
+        }
+        
         // Validate that constructor/destructor don't have conflicting attributes
         if self.attributes.is_constructor && self.attributes.is_destructor {
-            return Err(IDAError::ffi_with(
+            return Err(IDAError::validation(
                 "Function cannot be both constructor and destructor"
             ));
         }
-        
+        
+        if self.return_type.is_some() && self.return_register.is_some() {
+            return Err(IDAError::validation(
+                "Function cannot use both returns and returns_in_register"
+            ));
+        }
+        
+        Ok(())
+    }
+}
+
+impl TypeBuilder for FunctionBuilder {
+    fn build(self) -> Result<Type, IDAError> {
+        // Validate before building
+        TypeValidator::validate(&self)?;
+        
+        ensure_writable()?;
+
+        // Don't leave the types made along the way behind on failure
+        deleting_created_on_error(|| {
+            // Get return type ordinal
+            let return_type = self
+                .return_type
+                .as_ref()
+                .or(self.return_register.as_ref().map(|(ty, _)| ty));
+            let return_ordinal = match return_type {
+                Some(FieldType::Primitive(PrimitiveType::Void)) => 0, // void return
+                Some(return_type) => return_type.clone().into_ordinal("return types")?,
+                None => 0, // void return
+            };
+
+            // Register-assigned parameters/return values force a special calling convention
+            let calling_convention = if !self.calling_convention.is_special()
+                && (self.return_register.is_some()
+                    || self.parameters.iter().any(|p| p.register.is_some()))
+            {
+                CallingConvention::Special
+            } else {
+                self.calling_convention
+            };
+
+            // Create the function type
+            let func_ordinal = create_function_type(
+                return_ordinal,
+                calling_convention.to_ida_cc(),
+                self.is_vararg,
+            );
+
+            if func_ordinal == 0 {
+                return Err(ffi_error("Failed to create function type"));
+            }
+
+            // Add parameters
+            for param in self.parameters {
+                let param_ordinal = param.param_type.into_ordinal("parameter types")?;
+
+                if param_ordinal == 0 {
+                    return Err(ffi_error(format!(
+                        "Invalid type for parameter '{}'",
+                        param.name
+                    )));
+                }
+
+                let added = match &param.register {
+                    Some(reg_name) => add_function_parameter_in_register(
+                        func_ordinal,
+                        &param.name,
+                        param_ordinal,
+                        reg_name,
+                    ),
+                    None => add_function_parameter(
+                        func_ordinal,
+                        &param.name,
+                        param_ordinal,
+                        param.is_hidden,
+                    ),
+                };
+
+                if !added {
+                    return Err(ffi_error(format!(
+                        "Failed to add parameter '{}'",
+                        param.name
+                    )));
+                }
+            }
+
+            // Set function attributes
+            if !set_function_attributes(
+                func_ordinal,
+                self.attributes.is_noreturn,
+                self.attributes.is_pure,
+                self.attributes.is_static,
+                self.attributes.is_virtual,
+                self.attributes.is_const,
+                self.attributes.is_constructor,
+                self.attributes.is_destructor,
+            ) {
+                return Err(ffi_error("Failed to set function attributes"));
+            }
+
+            // The caller cleans the stack for cdecl, so there is nothing to purge
+            if let Some(purged) = self.purged_bytes
+                && !matches!(calling_convention, CallingConvention::Cdecl)
+                && !set_function_purged(func_ordinal, purged)
+            {
+                return Err(ffi_error(format!(
+                    "Failed to set function purged bytes to {}",
+                    purged
+                )));
+            }
+
+            if let Some((_, reg_name)) = &self.return_register
+                && !set_function_return_register(func_ordinal, reg_name)
+            {
+                return Err(ffi_error(format!(
+                    "Failed to set return register '{}'",
+                    reg_name
+                )));
+            }
+
+            if !self.spoiled.is_empty() && !set_function_spoiled(func_ordinal, &self.spoiled) {
+                return Err(ffi_error(format!(
+                    "Failed to set spoiled registers {:?}",
+                    self.spoiled
+                )));
+            }
+
+            Ok(Type::from_ordinal(func_ordinal))
+        })
+    }
+
+    fn build_boxed(self: Box<Self>, _registry: &mut TypeRegistry) -> Result<Type, IDAError> {
+        self.build()
+    }
+
+    fn check(&self) -> Result<(), IDAError> {
+        TypeValidator::validate(self)?;
+        let return_type = self
+            .return_type
+            .as_ref()
+            .or(self.return_register.as_ref().map(|(ty, _)| ty));
+        if let Some(return_type) = return_type {
+            return_type.check_resolvable("return types")?;
+        }
+        for param in &self.parameters {
+            param.param_type.check_resolvable("parameter types")?;
+        }
+        Ok(())
+    }
+}
+
+/// Builder for creating function pointer types
+#[derive(Debug, Clone)]
+pub struct FunctionPointerBuilder {
+    function_type: Type,
+}
+
+impl FunctionPointerBuilder {
+    /// Create a new function pointer builder
+    pub fn new(function_type: Type) -> Self {
+        Self { function_type }
+    }
+}
+
+impl TypeValidator for FunctionPointerBuilder {
+    fn validate(&self) -> Result<(), IDAError> {
+        let ordinal = self.function_type.ordinal();
+        if unsafe { idalib_is_valid_type_ordinal(ordinal) } && !is_function_type(ordinal) {
+            return Err(IDAError::validation(format!(
+                "Type #{ordinal} is not a function type"
+            )));
+        }
+        Ok(())
+    }
+}
+
+impl TypeBuilder for FunctionPointerBuilder {
+    fn build(self) -> Result<Type, IDAError> {
+        // Validate before building
+        TypeValidator::validate(&self)?;
+
+        ensure_writable()?;
+        let ptr_ordinal = create_function_pointer_type(self.function_type.ordinal());
+        
+        if ptr_ordinal == 0 {
+            return Err(ffi_error("Failed to create function pointer type"));
+        }
+        
+        Ok(Type::from_ordinal(ptr_ordinal))
+    }
+
+    fn build_boxed(self: Box<Self>, _registry: &mut TypeRegistry) -> Result<Type, IDAError> {
+        self.build()
+    }
+
+    fn check(&self) -> Result<(), IDAError> {
+        TypeValidator::validate(self)?;
+        FieldType::Existing(self.function_type.clone()).check_resolvable("function pointers")
+    }
+}
+
+/// Maps type names to the types created for them, so that struct/union
+/// builders can refer to each other through `FieldType::ForwardRef`
+#[derive(Debug, Clone, Default)]
+pub struct TypeRegistry {
+    types: std::collections::HashMap<String, Type>,
+    /// Names of types created empty by forward references and not built yet
+    placeholders: std::collections::HashSet<String>,
+    /// Typedef names mapped to the names of the types they alias
+    aliases: std::collections::HashMap<String, String>,
+    /// Types that existed before and were redefined in place by builders
+    /// with `replace_existing`, with their saved definitions; undoing a build
+    /// restores rather than deletes them
+    replaced: std::collections::HashMap<String, Vec<u8>>,
+}
+
+/// Names known to a `TypeRegistry` before a build, for undoing it
+pub(crate) struct RegistrySnapshot {
+    types: std::collections::HashSet<String>,
+    placeholders: std::collections::HashSet<String>,
+    ordinal_limit: u32,
+}
+
+impl TypeRegistry {
+    /// Create an empty registry
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Make `typ` resolvable under `name`
+    pub fn register(&mut self, name: impl Into<String>, typ: Type) {
+        self.types.insert(name.into(), typ);
+    }
+
+    /// Look up the type registered under `name`
+    pub fn lookup(&self, name: &str) -> Option<&Type> {
+        self.types.get(name)
+    }
+
+    /// Look up `name`, creating and registering an empty struct as a
+    /// placeholder if it is not known yet
+    fn resolve(&mut self, name: &str) -> Result<Type, IDAError> {
+        if let Some(typ) = self.lookup(name) {
+            return Ok(typ.clone());
+        }
+
+        ensure_writable()?;
+        let ordinal = create_struct_type(name);
+        if ordinal == 0 {
+            return Err(ffi_error(format!(
+                "Failed to create placeholder for '{}'",
+                name
+            )));
+        }
+
+        let typ = Type::from_ordinal(ordinal);
+        self.register(name, typ.clone());
+        self.placeholders.insert(name.to_owned());
+        Ok(typ)
+    }
+
+    /// Look up `name` to embed it by value, which requires it to be built
+    fn resolve_embedded(&self, name: &str) -> Result<Type, IDAError> {
+        match self.lookup(name) {
+            Some(typ) if !self.placeholders.contains(name) => Ok(typ.clone()),
+            _ => Err(IDAError::not_found_with(format!(
+                "'{}' must be built before it can be embedded by value",
+                name
+            ))),
+        }
+    }
+
+    /// Record the names known so far, so that a failed build can be undone
+    pub(crate) fn snapshot(&self) -> RegistrySnapshot {
+        RegistrySnapshot {
+            types: self.types.keys().cloned().collect(),
+            placeholders: self.placeholders.clone(),
+            ordinal_limit: ordinal_limit(),
+        }
+    }
+
+    /// Undo a failed build: delete the types created since `snapshot` was
+    /// taken, registered or not, and empty the placeholders it had started to
+    /// fill in
+    pub(crate) fn rollback(&mut self, snapshot: RegistrySnapshot) {
+        let added: Vec<String> = self
+            .types
+            .keys()
+            .filter(|name| !snapshot.types.contains(*name))
+            .cloned()
+            .collect();
+        for name in added {
+            if let Some(typ) = self.types.remove(&name) {
+                if let Some(original) = self.replaced.remove(&name) {
+                    restore_replaced_type(typ.ordinal(), &original);
+                } else {
+                    delete_type(typ.ordinal());
+                }
+            }
+            self.placeholders.remove(&name);
+        }
+        self.aliases.retain(|name, _| snapshot.types.contains(name));
+        // Helper types (pointers, arrays, gap arrays, inline structs, vtables)
+        // are never registered
+        delete_types_since(snapshot.ordinal_limit);
+
+        for name in snapshot.placeholders {
+            if !self.placeholders.contains(&name)
+                && let Some(typ) = self.types.get(&name)
+            {
+                clear_udt_members(typ.ordinal());
+                set_udt_kind(typ.ordinal(), false);
+                self.placeholders.insert(name);
+            }
+        }
+    }
+
+    /// Follow typedefs registered through `TypedefBuilder::build_into` to the
+    /// name of the type they alias
+    fn unalias<'a>(&'a self, mut name: &'a str) -> &'a str {
+        for _ in 0..self.aliases.len() {
+            match self.aliases.get(name) {
+                Some(target) => name = target,
+                None => break,
+            }
+        }
+        name
+    }
+
+    /// Order in which to add the members of `builders`, so that types
+    /// embedded by value are complete before they are embedded
+    fn embedding_order(&self, builders: &[StructBuilder]) -> Result<Vec<usize>, IDAError> {
+        fn visit(
+            registry: &TypeRegistry,
+            index: usize,
+            builders: &[StructBuilder],
+            visiting: &mut [bool],
+            order: &mut Vec<usize>,
+        ) -> Result<(), IDAError> {
+            if order.contains(&index) {
+                return Ok(());
+            }
+            if visiting[index] {
+                return Err(IDAError::validation(format!(
+                    "{} embeds itself by value through other types, which would give it infinite size",
+                    builders[index].name
+                )));
+            }
+            visiting[index] = true;
+            for field in &builders[index].fields {
+                if let Some(name) = field.field_type.embedded_ref()
+                    && let name = registry.unalias(name)
+                    && let Some(dep) = builders.iter().position(|b| b.name == name)
+                {
+                    visit(registry, dep, builders, visiting, order)?;
+                }
+            }
+            order.push(index);
+            Ok(())
+        }
+
+        let mut visiting = vec![false; builders.len()];
+        let mut order = Vec::with_capacity(builders.len());
+        for index in 0..builders.len() {
+            visit(self, index, builders, &mut visiting, &mut order)?;
+        }
+        Ok(order)
+    }
+
+    /// Build a set of structs/unions that may refer to each other
+    ///
+    /// All types are first created empty and registered, then their members
+    /// are added, so pointers to any type of the set can be resolved. Types
+    /// embedded by value through `ForwardRef` get their members first. Types
+    /// are returned in the order of `builders`.
+    pub fn build_all(
+        &mut self,
+        builders: impl IntoIterator<Item = StructBuilder>,
+    ) -> Result<Vec<Type>, IDAError> {
+        let builders: Vec<StructBuilder> = builders.into_iter().collect();
+        for builder in &builders {
+            TypeValidator::validate(builder)?;
+        }
+        let order = self.embedding_order(&builders)?;
+
+        // Don't leave partially built types behind
+        let snapshot = self.snapshot();
+        self.create_in_order(builders, &order)
+            .inspect_err(|_| self.rollback(snapshot))
+    }
+
+    /// Create `builders` empty, then add their members in `order`
+    fn create_in_order(
+        &mut self,
+        builders: Vec<StructBuilder>,
+        order: &[usize],
+    ) -> Result<Vec<Type>, IDAError> {
+        let mut ordinals = Vec::with_capacity(builders.len());
+        for builder in &builders {
+            ordinals.push(builder.create_registered(self)?);
+        }
+
+        let mut pending: Vec<Option<StructBuilder>> = builders.into_iter().map(Some).collect();
+        let mut types: Vec<Option<Type>> = vec![None; pending.len()];
+        for &index in order {
+            if let Some(builder) = pending[index].take() {
+                types[index] = Some(builder.populate(ordinals[index], Some(self))?);
+            }
+        }
+        Ok(types.into_iter().flatten().collect())
+    }
+}
+
+/// Builder for creating typedefs (named aliases of other types)
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TypedefBuilder {
+    name: String,
+    target_type: FieldType,
+}
+
+impl TypedefBuilder {
+    /// Create a new typedef builder aliasing `underlying` as `name`
+    ///
+    /// Aliasing a named type under its own name (`typedef struct Foo Foo;`)
+    /// yields the named type itself.
+    pub fn new(name: impl Into<String>, underlying: impl Into<FieldType>) -> Self {
+        Self {
+            name: name.into(),
+            target_type: underlying.into(),
+        }
+    }
+
+    /// Get the name of the typedef being built
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Build the typedef, resolving forward references in its target through
+    /// `registry`, and register it there
+    ///
+    /// A typedef of a forward reference only names its target, so the target
+    /// may be built later into the same registry; struct/union builders that
+    /// embed the typedef are then ordered after the target.
+    pub fn build_into(self, registry: &mut TypeRegistry) -> Result<Type, IDAError> {
+        TypeValidator::validate(&self)?;
+        let snapshot = registry.snapshot();
+        let target_type = match self.target_type {
+            FieldType::ForwardRef(target) => {
+                registry.aliases.insert(self.name.clone(), target.clone());
+                registry.resolve(&target).map(FieldType::Existing)
+            }
+            target_type => target_type.resolved(registry),
+        };
+        let typ = target_type
+            .and_then(|target_type| TypedefBuilder::new(self.name.clone(), target_type).build())
+            .inspect_err(|_| registry.rollback(snapshot))?;
+        registry.register(self.name, typ.clone());
+        Ok(typ)
+    }
+}
+
+impl TypeValidator for TypedefBuilder {
+    fn validate(&self) -> Result<(), IDAError> {
+        if self.name.is_empty() {
+            return Err(IDAError::validation("Typedef name cannot be empty"));
+        }
         Ok(())
     }
 }
 
-impl TypeBuilder for FunctionBuilder {
+impl TypeBuilder for TypedefBuilder {
     fn build(self) -> Result<Type, IDAError> {
         // Validate before building
         TypeValidator::validate(&self)?;
-        
-        // Get return type ordinal
-        let return_ordinal = match self.return_type {
-            Some(FieldType::Primitive(prim)) => get_primitive_type_ordinal(prim.to_ida_type()),
-            Some(FieldType::Existing(typ)) => typ.ordinal(),
-            Some(FieldType::ForwardRef(_)) => {
-                return Err(IDAError::ffi_with(
-                    "Forward references not supported in return types"
-                ));
-            }
-            None => 0, // void return
-        };
-        
-        // Create the function type
-        let func_ordinal = create_function_type(
-            return_ordinal,
-            self.calling_convention.to_ida_cc(),
-            self.is_vararg,
-        );
-        
-        if func_ordinal == 0 {
-            return Err(IDAError::ffi_with("Failed to create function type"));
-        }
-        
-        // Add parameters
-        for param in self.parameters {
-            let param_ordinal = match param.param_type {
-                FieldType::Primitive(prim) => get_primitive_type_ordinal(prim.to_ida_type()),
-                FieldType::Existing(typ) => typ.ordinal(),
-                FieldType::ForwardRef(_) => {
-                    return Err(IDAError::ffi_with(
-                        "Forward references not supported in parameter types"
-                    ));
-                }
-            };
-            
-            if param_ordinal == 0 {
-                return Err(IDAError::ffi_with(format!(
-                    "Invalid type for parameter '{}'",
-                    param.name
+
+        ensure_writable()?;
+
+        // Don't leave the types made along the way behind on failure
+        deleting_created_on_error(|| {
+            // Get the target type ordinal
+            let target_ordinal = self.target_type.into_ordinal("typedef targets")?;
+
+            if target_ordinal == 0 {
+                return Err(ffi_error(format!(
+                    "Invalid target type for typedef '{}'",
+                    self.name
                 )));
             }
-            
-            if !add_function_parameter(
-                func_ordinal,
-                &param.name,
-                param_ordinal,
-                param.is_hidden,
-            ) {
-                return Err(IDAError::ffi_with(format!(
-                    "Failed to add parameter '{}'",
-                    param.name
+
+            let typedef_ordinal = create_typedef(&self.name, target_ordinal);
+            if typedef_ordinal == 0 {
+                return Err(ffi_error(format!(
+                    "Failed to create typedef '{}'",
+                    self.name
                 )));
             }
-        }
-        
-        // Set function attributes
-        if !set_function_attributes(
-            func_ordinal,
-            self.attributes.is_noreturn,
-            self.attributes.is_pure,
-            self.attributes.is_static,
-            self.attributes.is_virtual,
-            self.attributes.is_const,
-            self.attributes.is_constructor,
-            self.attributes.is_destructor,
-        ) {
-            return Err(IDAError::ffi_with("Failed to set function attributes"));
-        }
-        
-        Ok(Type::from_ordinal(func_ordinal))
+
+            Ok(Type::from_ordinal(typedef_ordinal))
+        })
     }
-}
 
-/// Builder for creating function pointer types
-#[derive(Debug, Clone)]
-pub struct FunctionPointerBuilder {
-    function_type: Type,
-}
+    fn build_boxed(self: Box<Self>, registry: &mut TypeRegistry) -> Result<Type, IDAError> {
+        self.build_into(registry)
+    }
 
-impl FunctionPointerBuilder {
-    /// Create a new function pointer builder
-    pub fn new(function_type: Type) -> Self {
-        Self { function_type }
+    fn defined_name(&self) -> Option<&str> {
+        Some(&self.name)
     }
-}
 
-impl TypeBuilder for FunctionPointerBuilder {
-    fn build(self) -> Result<Type, IDAError> {
-        let ptr_ordinal = create_function_pointer_type(self.function_type.ordinal());
-        
-        if ptr_ordinal == 0 {
-            return Err(IDAError::ffi_with("Failed to create function pointer type"));
-        }
-        
-        Ok(Type::from_ordinal(ptr_ordinal))
+    fn embedded_refs(&self) -> Vec<&str> {
+        self.target_type.embedded_ref().into_iter().collect()
+    }
+
+    fn check(&self) -> Result<(), IDAError> {
+        TypeValidator::validate(self)?;
+        self.target_type.check_resolvable("typedef targets")
     }
 }
 
@@ -903,6 +3813,29 @@ pub mod builders {
         StructBuilder::new(name)
     }
 
+    /// Create a new C++ class builder (see `StructBuilder::as_class`)
+    pub fn class_type(name: impl Into<String>) -> StructBuilder {
+        StructBuilder::new(name).as_class()
+    }
+
+    /// Declare `struct name;` without defining it, so pointers to it can be
+    /// created before its members are known
+    ///
+    /// Building a struct or union of the same name later completes the
+    /// declaration in place: it keeps the ordinal, so pointers created in
+    /// the meantime refer to the full definition.
+    pub fn forward_declare_struct(name: impl Into<String>) -> Result<Type, IDAError> {
+        let name = name.into();
+        if name.is_empty() {
+            return Err(IDAError::validation("Forward declared struct name cannot be empty"));
+        }
+        ensure_writable()?;
+        match super::forward_declare_struct(&name) {
+            0 => Err(ffi_error(format!("Failed to forward declare struct '{}'", name))),
+            ordinal => Ok(Type::from_ordinal(ordinal)),
+        }
+    }
+
     /// Create a new union builder
     pub fn union_type(name: impl Into<String>) -> StructBuilder {
         StructBuilder::new_union(name)
@@ -918,6 +3851,11 @@ pub mod builders {
         ArrayBuilder::new(element_type, num_elements)
     }
 
+    /// Create a new multi-dimensional array builder, outermost dimension first
+    pub fn array_nd(element_type: impl Into<FieldType>, dimensions: &[u32]) -> ArrayBuilder {
+        ArrayBuilder::new(element_type, 0).dimensions(dimensions)
+    }
+
     /// Create a new pointer builder
     pub fn pointer_type(target_type: impl Into<FieldType>) -> PointerBuilder {
         PointerBuilder::new(target_type)
@@ -933,6 +3871,117 @@ pub mod builders {
         FunctionPointerBuilder::new(function_type)
     }
 
+    /// Create a new typedef builder
+    pub fn typedef(name: impl Into<String>, target: impl Into<FieldType>) -> TypedefBuilder {
+        TypedefBuilder::new(name, target)
+    }
+
+    /// Build builders of any kind that refer to each other by name through
+    /// `FieldType::ForwardRef`, in whatever order they are given
+    ///
+    /// Types embedded by value are built before the types embedding them;
+    /// pointers may refer to types built later, which are created empty
+    /// first. Types that embed each other by value cannot be built and are
+    /// reported as an error. Types are returned in the order of `builders`,
+    /// and if any build fails the types built so far are deleted again.
+    pub fn build_all(builders: Vec<Box<dyn TypeBuilder>>) -> Result<Vec<Type>, IDAError> {
+        let order = build_order(&builders)?;
+
+        let mut registry = TypeRegistry::new();
+        let snapshot = registry.snapshot();
+        let mut pending: Vec<Option<Box<dyn TypeBuilder>>> =
+            builders.into_iter().map(Some).collect();
+        let mut types: Vec<Option<Type>> = vec![None; pending.len()];
+        for index in order {
+            if let Some(builder) = pending[index].take() {
+                match builder.build_boxed(&mut registry) {
+                    Ok(typ) => types[index] = Some(typ),
+                    Err(e) => {
+                        registry.rollback(snapshot);
+                        return Err(e);
+                    }
+                }
+            }
+        }
+        Ok(types.into_iter().flatten().collect())
+    }
+
+    /// Order in which to build `builders`, so that types embedded by value
+    /// are built before they are embedded
+    fn build_order(builders: &[Box<dyn TypeBuilder>]) -> Result<Vec<usize>, IDAError> {
+        fn visit(
+            index: usize,
+            builders: &[Box<dyn TypeBuilder>],
+            visiting: &mut [bool],
+            order: &mut Vec<usize>,
+        ) -> Result<(), IDAError> {
+            if order.contains(&index) {
+                return Ok(());
+            }
+            if visiting[index] {
+                return Err(IDAError::validation(format!(
+                    "{} embeds itself by value through other types; break the cycle with a pointer",
+                    builders[index].defined_name().unwrap_or("<unnamed>")
+                )));
+            }
+            visiting[index] = true;
+            for name in builders[index].embedded_refs() {
+                if let Some(dep) = builders.iter().position(|b| b.defined_name() == Some(name)) {
+                    visit(dep, builders, visiting, order)?;
+                }
+            }
+            order.push(index);
+            Ok(())
+        }
+
+        let mut visiting = vec![false; builders.len()];
+        let mut order = Vec::with_capacity(builders.len());
+        for index in 0..builders.len() {
+            visit(index, builders, &mut visiting, &mut order)?;
+        }
+        Ok(order)
+    }
+
+    /// Create a named function pointer type, like
+    /// `typedef int (*callback_t)(int, void *)`, from the function type
+    /// defined by `define`
+    ///
+    /// ```ignore
+    /// let callback = builders::callback_typedef("callback_t", |f| {
+    ///     f.returns(builders::int32())
+    ///         .param("value", builders::int32())
+    ///         .param("context", FieldType::Pointer(Box::new(builders::void().into())))
+    /// })?;
+    /// ```
+    pub fn callback_typedef(
+        name: impl Into<String>,
+        define: impl FnOnce(FunctionBuilder) -> FunctionBuilder,
+    ) -> Result<Type, IDAError> {
+        let function_type = define(FunctionBuilder::new()).build()?;
+        let pointer_type = FunctionPointerBuilder::new(function_type).build()?;
+        TypedefBuilder::new(name, pointer_type).build()
+    }
+
+    /// Get the Windows `GUID` struct, creating it if the database has no type
+    /// of that name yet:
+    /// `struct GUID { uint32 Data1; uint16 Data2; uint16 Data3; uint8 Data4[8]; }`
+    pub fn guid_type() -> Result<Type, IDAError> {
+        match get_type_ordinal_by_name("GUID") {
+            0 => struct_type("GUID")
+                .field("Data1", uint32())
+                .field("Data2", uint16())
+                .field("Data3", uint16())
+                .field("Data4", FieldType::Array(Box::new(uint8().into()), 8))
+                .build(),
+            ordinal => Ok(Type::from_ordinal(ordinal)),
+        }
+    }
+
+    /// Get the `GUID` struct under its other name; see `guid_type`
+    pub fn uuid_type() -> Result<Type, IDAError> {
+        guid_type()
+    }
+
     /// Create primitive type builders
     pub fn int8() -> PrimitiveType {
         PrimitiveType::Int8
@@ -950,6 +3999,10 @@ pub mod builders {
         PrimitiveType::Int64
     }
 
+    pub fn int128() -> PrimitiveType {
+        PrimitiveType::Int128
+    }
+
     pub fn uint8() -> PrimitiveType {
         PrimitiveType::UInt8
     }
@@ -966,6 +4019,14 @@ pub mod builders {
         PrimitiveType::UInt64
     }
 
+    pub fn uint128() -> PrimitiveType {
+        PrimitiveType::UInt128
+    }
+
+    pub fn float16() -> PrimitiveType {
+        PrimitiveType::Float16
+    }
+
     pub fn float() -> PrimitiveType {
         PrimitiveType::Float
     }
@@ -974,6 +4035,10 @@ pub mod builders {
         PrimitiveType::Double
     }
 
+    pub fn long_double() -> PrimitiveType {
+        PrimitiveType::LongDouble
+    }
+
     pub fn void() -> PrimitiveType {
         PrimitiveType::Void
     }
@@ -982,7 +4047,536 @@ pub mod builders {
         PrimitiveType::Char
     }
 
+    pub fn signed_char() -> PrimitiveType {
+        PrimitiveType::SignedChar
+    }
+
+    pub fn wchar() -> PrimitiveType {
+        PrimitiveType::WChar
+    }
+
+    pub fn char16() -> PrimitiveType {
+        PrimitiveType::Char16
+    }
+
+    pub fn char32() -> PrimitiveType {
+        PrimitiveType::Char32
+    }
+
     pub fn bool() -> PrimitiveType {
         PrimitiveType::Bool
     }
-}
\ No newline at end of file
+
+    /// Named C99/POSIX typedefs (`int32_t`, `size_t`, ...)
+    ///
+    /// Each helper returns the existing type of that name if the database already
+    /// has one, otherwise it creates the typedef. Pointer-sized typedefs follow
+    /// the database's pointer size.
+    pub mod stdint {
+        use super::*;
+
+        fn typedef(name: &str, prim: PrimitiveType) -> Result<Type, IDAError> {
+            ensure_writable()?;
+            let Some(code) = prim.to_ida_type() else {
+                return Err(IDAError::unsupported(format!(
+                    "{:?} cannot be the target of typedef '{}'",
+                    prim, name
+                )));
+            };
+            let ordinal = get_or_create_primitive_typedef(name, code);
+            if ordinal == 0 {
+                return Err(ffi_error(format!(
+                    "Failed to create typedef '{}'",
+                    name
+                )));
+            }
+            Ok(Type::from_ordinal(ordinal))
+        }
+
+        fn pointer_sized(signed: bool) -> PrimitiveType {
+            match (get_pointer_size(), signed) {
+                (8, true) => PrimitiveType::Int64,
+                (8, false) => PrimitiveType::UInt64,
+                (2, true) => PrimitiveType::Int16,
+                (2, false) => PrimitiveType::UInt16,
+                (_, true) => PrimitiveType::Int32,
+                (_, false) => PrimitiveType::UInt32,
+            }
+        }
+
+        pub fn int8_t() -> Result<Type, IDAError> {
+            typedef("int8_t", PrimitiveType::Int8)
+        }
+
+        pub fn int16_t() -> Result<Type, IDAError> {
+            typedef("int16_t", PrimitiveType::Int16)
+        }
+
+        pub fn int32_t() -> Result<Type, IDAError> {
+            typedef("int32_t", PrimitiveType::Int32)
+        }
+
+        pub fn int64_t() -> Result<Type, IDAError> {
+            typedef("int64_t", PrimitiveType::Int64)
+        }
+
+        pub fn uint8_t() -> Result<Type, IDAError> {
+            typedef("uint8_t", PrimitiveType::UInt8)
+        }
+
+        pub fn uint16_t() -> Result<Type, IDAError> {
+            typedef("uint16_t", PrimitiveType::UInt16)
+        }
+
+        pub fn uint32_t() -> Result<Type, IDAError> {
+            typedef("uint32_t", PrimitiveType::UInt32)
+        }
+
+        pub fn uint64_t() -> Result<Type, IDAError> {
+            typedef("uint64_t", PrimitiveType::UInt64)
+        }
+
+        pub fn size_t() -> Result<Type, IDAError> {
+            typedef("size_t", pointer_sized(false))
+        }
+
+        pub fn ssize_t() -> Result<Type, IDAError> {
+            typedef("ssize_t", pointer_sized(true))
+        }
+
+        pub fn uintptr_t() -> Result<Type, IDAError> {
+            typedef("uintptr_t", pointer_sized(false))
+        }
+
+        pub fn intptr_t() -> Result<Type, IDAError> {
+            typedef("intptr_t", pointer_sized(true))
+        }
+    }
+    /// Windows SDK typedefs (`DWORD`, `HANDLE`, `LPCWSTR`, ...)
+    ///
+    /// Each helper returns the existing type of that name if the database or
+    /// its type libraries already have one, otherwise it creates the typedef.
+    /// Handles are `void *` typedefs, and pointer-sized integers follow the
+    /// database's pointer size.
+    pub mod win32 {
+        use super::*;
+
+        /// Get the type called `name`, or create it as a typedef of `target`
+        fn typedef(
+            name: &str,
+            target: impl FnOnce() -> Result<FieldType, IDAError>,
+        ) -> Result<Type, IDAError> {
+            match get_type_ordinal_by_name(name) {
+                0 => TypedefBuilder::new(name, target()?).build(),
+                ordinal => Ok(Type::from_ordinal(ordinal)),
+            }
+        }
+
+        fn primitive(name: &str, prim: PrimitiveType) -> Result<Type, IDAError> {
+            typedef(name, || Ok(prim.into()))
+        }
+
+        /// Typedef of a pointer to `target`, optionally to a const `target`
+        fn pointer(name: &str, target: PrimitiveType, is_const: bool) -> Result<Type, IDAError> {
+            typedef(name, || {
+                let target = FieldType::from(target).qualified(is_const, false)?;
+                Ok(FieldType::Pointer(Box::new(target)))
+            })
+        }
+
+        fn pointer_sized(name: &str, signed: bool) -> Result<Type, IDAError> {
+            let prim = match (get_pointer_size(), signed) {
+                (8, true) => PrimitiveType::Int64,
+                (8, false) => PrimitiveType::UInt64,
+                (_, true) => PrimitiveType::Int32,
+                (_, false) => PrimitiveType::UInt32,
+            };
+            primitive(name, prim)
+        }
+
+        pub fn byte() -> Result<Type, IDAError> {
+            primitive("BYTE", PrimitiveType::UInt8)
+        }
+
+        pub fn word() -> Result<Type, IDAError> {
+            primitive("WORD", PrimitiveType::UInt16)
+        }
+
+        pub fn dword() -> Result<Type, IDAError> {
+            primitive("DWORD", PrimitiveType::UInt32)
+        }
+
+        pub fn qword() -> Result<Type, IDAError> {
+            primitive("QWORD", PrimitiveType::UInt64)
+        }
+
+        /// `BOOL`, a 4-byte integer; see `boolean` for the 1-byte `BOOLEAN`
+        pub fn bool() -> Result<Type, IDAError> {
+            primitive("BOOL", PrimitiveType::Int32)
+        }
+
+        pub fn boolean() -> Result<Type, IDAError> {
+            primitive("BOOLEAN", PrimitiveType::UInt8)
+        }
+
+        pub fn int() -> Result<Type, IDAError> {
+            primitive("INT", PrimitiveType::Int32)
+        }
+
+        pub fn uint() -> Result<Type, IDAError> {
+            primitive("UINT", PrimitiveType::UInt32)
+        }
+
+        pub fn long() -> Result<Type, IDAError> {
+            primitive("LONG", PrimitiveType::Int32)
+        }
+
+        pub fn ulong() -> Result<Type, IDAError> {
+            primitive("ULONG", PrimitiveType::UInt32)
+        }
+
+        pub fn hresult() -> Result<Type, IDAError> {
+            typedef("HRESULT", || Ok(FieldType::Existing(long()?)))
+        }
+
+        pub fn wchar() -> Result<Type, IDAError> {
+            primitive("WCHAR", PrimitiveType::Char16)
+        }
+
+        pub fn ulong_ptr() -> Result<Type, IDAError> {
+            pointer_sized("ULONG_PTR", false)
+        }
+
+        pub fn long_ptr() -> Result<Type, IDAError> {
+            pointer_sized("LONG_PTR", true)
+        }
+
+        pub fn size_t() -> Result<Type, IDAError> {
+            pointer_sized("SIZE_T", false)
+        }
+
+        pub fn lpvoid() -> Result<Type, IDAError> {
+            pointer("LPVOID", PrimitiveType::Void, false)
+        }
+
+        pub fn lpcvoid() -> Result<Type, IDAError> {
+            pointer("LPCVOID", PrimitiveType::Void, true)
+        }
+
+        pub fn lpstr() -> Result<Type, IDAError> {
+            pointer("LPSTR", PrimitiveType::Char, false)
+        }
+
+        pub fn lpcstr() -> Result<Type, IDAError> {
+            pointer("LPCSTR", PrimitiveType::Char, true)
+        }
+
+        pub fn lpwstr() -> Result<Type, IDAError> {
+            pointer("LPWSTR", PrimitiveType::Char16, false)
+        }
+
+        pub fn lpcwstr() -> Result<Type, IDAError> {
+            pointer("LPCWSTR", PrimitiveType::Char16, true)
+        }
+
+        pub fn handle() -> Result<Type, IDAError> {
+            pointer("HANDLE", PrimitiveType::Void, false)
+        }
+
+        /// Typedef of `HANDLE` for a specific kind of handle
+        fn handle_typedef(name: &str) -> Result<Type, IDAError> {
+            typedef(name, || Ok(FieldType::Existing(handle()?)))
+        }
+
+        pub fn hwnd() -> Result<Type, IDAError> {
+            handle_typedef("HWND")
+        }
+
+        pub fn hmodule() -> Result<Type, IDAError> {
+            handle_typedef("HMODULE")
+        }
+
+        pub fn hinstance() -> Result<Type, IDAError> {
+            handle_typedef("HINSTANCE")
+        }
+
+        pub fn hkey() -> Result<Type, IDAError> {
+            handle_typedef("HKEY")
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::IDAErrorKind;
+
+    #[test]
+    fn primitive_codes_round_trip() {
+        // WChar and Float16 are named typedefs and SignedChar shares Int8's code
+        let primitives = [
+            PrimitiveType::Void,
+            PrimitiveType::Int8,
+            PrimitiveType::Int16,
+            PrimitiveType::Int32,
+            PrimitiveType::Int64,
+            PrimitiveType::Int128,
+            PrimitiveType::UInt8,
+            PrimitiveType::UInt16,
+            PrimitiveType::UInt32,
+            PrimitiveType::UInt64,
+            PrimitiveType::UInt128,
+            PrimitiveType::Float,
+            PrimitiveType::Double,
+            PrimitiveType::LongDouble,
+            PrimitiveType::Char,
+            PrimitiveType::Char16,
+            PrimitiveType::Char32,
+            PrimitiveType::Bool,
+        ];
+        for prim in primitives {
+            let code = prim.to_ida_type().unwrap();
+            assert_eq!(PrimitiveType::from_ida_type(code), Some(prim));
+        }
+        assert_eq!(
+            PrimitiveType::from_ida_type(PrimitiveType::SignedChar.to_ida_type().unwrap()),
+            Some(PrimitiveType::Int8)
+        );
+        assert_eq!(PrimitiveType::Float16.to_ida_type(), None);
+        assert_eq!(PrimitiveType::from_ida_type(0x39), None);
+    }
+
+    #[test]
+    fn half_float_arrays() {
+        let halves = FieldType::Array(Box::new(FieldType::Primitive(PrimitiveType::Float16)), 8);
+        assert_eq!(halves.layout(), Some((16, 2)));
+        assert_eq!(PrimitiveType::Float16.width(), Some(2));
+    }
+
+    #[test]
+    fn calling_convention_codes() {
+        let conventions = [
+            CallingConvention::Unknown,
+            CallingConvention::Cdecl,
+            CallingConvention::Stdcall,
+            CallingConvention::Pascal,
+            CallingConvention::Fastcall,
+            CallingConvention::Thiscall,
+            CallingConvention::Swift,
+            CallingConvention::Golang,
+            CallingConvention::Special,
+            CallingConvention::SpecialEllipsis,
+            CallingConvention::SpecialPurge,
+        ];
+        for cc in conventions {
+            let decoded = CallingConvention::from_ida_cc(cc.to_ida_cc());
+            assert_eq!(decoded.to_ida_cc(), cc.to_ida_cc());
+        }
+
+        // Only the convention bits are looked at
+        assert!(matches!(
+            CallingConvention::from_ida_cc(0x33),
+            CallingConvention::Cdecl
+        ));
+        assert!(matches!(
+            CallingConvention::from_ida_cc(CallingConvention::Vectorcall.to_ida_cc()),
+            CallingConvention::Special
+        ));
+        assert!(matches!(
+            CallingConvention::from_ida_cc(0x20),
+            CallingConvention::Custom(0x20)
+        ));
+    }
+
+    #[test]
+    fn fastcall_member_functions() {
+        let this = || FieldType::Pointer(Box::new(FieldType::ForwardRef("Widget".into())));
+        let method = FunctionBuilder::new()
+            .calling_convention(CallingConvention::Fastcall)
+            .hidden_param("this", this())
+            .param("value", PrimitiveType::Int32);
+
+        assert!(TypeValidator::validate(&method).is_ok());
+        assert!(TypeValidator::validate(&method.clone().virtual_func()).is_ok());
+
+        let late_this = FunctionBuilder::new()
+            .calling_convention(CallingConvention::Fastcall)
+            .param("value", PrimitiveType::Int32)
+            .hidden_param("this", this());
+        assert_eq!(
+            TypeValidator::validate(&late_this).unwrap_err().kind(),
+            IDAErrorKind::Validation
+        );
+    }
+
+    #[test]
+    fn struct_builder_members() {
+        let builder = StructBuilder::new("Flags")
+            .field("a", PrimitiveType::UInt32)
+            .bitfield("f", 32, 3, true)
+            .field("b", PrimitiveType::UInt32);
+        assert_eq!(builder.field_count(), 3);
+        assert_eq!(builder.field_names(), ["a", "b", "f"]);
+
+        let empty = StructBuilder::new("Empty");
+        assert_eq!(empty.field_count(), 0);
+        assert!(empty.field_names().is_empty());
+    }
+
+    #[test]
+    fn bitfields_stay_after_their_fields() {
+        let builder = StructBuilder::new("Derived")
+            .field("a", FieldType::forward("A"))
+            .bitfield("f", 0, 3, true)
+            .field("b", FieldType::forward("B"));
+        assert_eq!(builder.bitfields[0].fields_before, Some(1));
+
+        // The base class goes in front of every member
+        let builder = builder.base_class(Type::from_ordinal(1));
+        assert_eq!(builder.field_names()[..3], ["baseclass_0", "a", "b"]);
+        assert_eq!(builder.bitfields[0].fields_before, Some(2));
+    }
+
+    #[test]
+    fn computed_size_of_forward_declared_member() {
+        let builder = StructBuilder::new("Outer")
+            .field("inner", FieldType::forward("Inner"))
+            .field("tail", FieldType::Array(Box::new(FieldType::forward("Inner")), 4));
+        assert_eq!(builder.computed_size(), None);
+    }
+
+    #[test]
+    fn no_offsets_guessed_past_forward_declared_member() {
+        let builder = StructBuilder::new("Outer")
+            .field("inner", FieldType::forward("Inner"))
+            .field("after", FieldType::forward("Inner"))
+            .field_at("placed", FieldType::forward("Inner"), 64);
+        assert_eq!(
+            builder.field_extents(),
+            [(Some(0), None), (None, None), (Some(64), None)]
+        );
+    }
+
+    #[test]
+    fn struct_type_macro() {
+        let node = crate::struct_type!(Node {
+            value: int32,
+            name: array(char, 16),
+            next: ptr(Self),
+        });
+        assert_eq!(node.name(), "Node");
+        assert_eq!(node.field_names(), ["value", "name", "next"]);
+        assert!(matches!(
+            node.fields[0].field_type,
+            FieldType::Primitive(PrimitiveType::Int32)
+        ));
+        assert!(matches!(
+            &node.fields[1].field_type,
+            FieldType::Array(element, 16)
+                if matches!(**element, FieldType::Primitive(PrimitiveType::Char))
+        ));
+        assert!(matches!(
+            &node.fields[2].field_type,
+            FieldType::Pointer(target)
+                if matches!(&**target, FieldType::ForwardRef(name) if name == "Node")
+        ));
+    }
+
+    #[test]
+    fn enum_type_macro() {
+        let shade = crate::enum_type!(Shade: 1 { LIGHT = 1, MEDIUM, DARK });
+        assert_eq!(shade.name(), "Shade");
+        assert_eq!(shade.member_value("LIGHT"), Some(1));
+        assert_eq!(shade.member_value("MEDIUM"), Some(2));
+        assert_eq!(shade.member_value("DARK"), Some(3));
+
+        let flags = crate::enum_type!(bitmask Flags: 4 { A, B, C, EXEC = 0x10, D });
+        assert!(flags.is_bitmask);
+        let values: Vec<i64> = flags.members.iter().map(|member| member.value).collect();
+        assert_eq!(values, [1, 2, 4, 0x10, 0x20]);
+        assert!(TypeValidator::validate(&flags).is_ok());
+
+        // Running out of bits leaves a value that validation rejects
+        let full = EnumBuilder::new("Full", 8)
+            .bitmask(true)
+            .member("TOP", i64::MIN)
+            .auto_member("PAST");
+        assert_eq!(full.member_value("PAST"), Some(0));
+        assert!(TypeValidator::validate(&full).is_err());
+    }
+
+    #[test]
+    fn type_steps_describe_field_types() {
+        let steps = TypeStep::parse_all(vec![
+            "pointer".to_owned(),
+            "array 4".to_owned(),
+            "primitive 20 int32_t".to_owned(),
+        ]);
+        assert_eq!(
+            steps,
+            [
+                TypeStep::Pointer,
+                TypeStep::Array(4),
+                TypeStep::Primitive(0x14, "int32_t".to_owned())
+            ]
+        );
+        let field_type = FieldType::from_steps(&steps, &mut |_| None);
+        assert!(matches!(
+            field_type,
+            Some(FieldType::Pointer(target))
+                if matches!(&*target, FieldType::Array(element, 4)
+                    if matches!(**element, FieldType::Primitive(PrimitiveType::Int32)))
+        ));
+
+        // Types without a primitive of their own are kept as expressions
+        let steps = TypeStep::parse_all(vec![
+            "primitive 7 int".to_owned(),
+            "decl int (__cdecl *)(int)".to_owned(),
+        ]);
+        assert_eq!(
+            steps[1],
+            TypeStep::Declared("int (__cdecl *)(int)".to_owned())
+        );
+        assert!(matches!(
+            FieldType::from_steps(&steps[..1], &mut |_| None),
+            Some(FieldType::Declared(expr)) if expr == "int"
+        ));
+
+        // Numbered types are left to the caller, which may fail
+        let steps = [TypeStep::Pointer, TypeStep::Numbered(5)];
+        assert!(FieldType::from_steps(&steps, &mut |_| None).is_none());
+        assert!(matches!(
+            FieldType::from_steps(&steps, &mut |ordinal| Some(FieldType::Existing(
+                Type::from_ordinal(ordinal)
+            ))),
+            Some(FieldType::Pointer(target))
+                if matches!(&*target, FieldType::Existing(typ) if typ.ordinal() == 5)
+        ));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn minimal_json_builders() {
+        let builder: StructBuilder = serde_json::from_str(
+            r#"{"name": "Pair", "fields": [
+                {"name": "a", "field_type": {"Primitive": "Int32"}},
+                {"name": "b", "field_type": {"Primitive": "Int32"}, "offset": 4}
+            ]}"#,
+        )
+        .unwrap();
+        assert_eq!(builder.field_names(), ["a", "b"]);
+        assert!(!builder.is_union && !builder.fill_gaps && !builder.fields[0].is_flexible);
+        assert_eq!(builder.fields[1].offset, Some(4));
+
+        let builder: EnumBuilder = serde_json::from_str(r#"{"name": "Mode", "width": 4}"#).unwrap();
+        let builder = builder.auto_member("OFF").auto_member("ON");
+        let values: Vec<i64> = builder.members.iter().map(|member| member.value).collect();
+        assert_eq!(values, [0, 1]);
+
+        let builder: PointerBuilder =
+            serde_json::from_str(r#"{"target_type": {"Primitive": "Void"}}"#).unwrap();
+        assert!(!builder.const_target && !builder.volatile_target);
+        assert_eq!(builder.size_bytes, None);
+        assert_eq!(builder.model, PointerModel::Default);
+    }
+}
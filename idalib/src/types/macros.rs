@@ -0,0 +1,148 @@
+//! Declarative macros for defining types in a near-C syntax
+
+/// Start building a struct from a near-C member list
+///
+/// Expands to a `StructBuilder` with one `field` call per member, so it can
+/// be refined further before calling `build`:
+///
+/// ```ignore
+/// let node = struct_type!(Node {
+///     value: int32,
+///     name: array(char, 16),
+///     next: ptr(Self),
+///     data: { data_type.clone() },
+/// })
+/// .build()?;
+/// ```
+///
+/// Members are typed by:
+/// - a primitive name, like `int32` or `double` (see `builders`)
+/// - `ptr(T)`, a pointer to `T`; `ptr(Self)` points to the struct itself
+/// - `array(T, N)`, an array of `N` elements of `T`
+/// - `{ expr }`, any expression convertible into a `FieldType`
+#[macro_export]
+macro_rules! struct_type {
+    ($name:ident { $($body:tt)* }) => {
+        $crate::__struct_type_fields!(
+            $name;
+            $crate::types::builders::struct_type(stringify!($name));
+            $($body)*
+        )
+    };
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __struct_type_fields {
+    ($name:ident; $builder:expr;) => {
+        $builder
+    };
+    ($name:ident; $builder:expr; $field:ident : { $ty:expr } $(, $($rest:tt)*)?) => {
+        $crate::__struct_type_fields!(
+            $name;
+            $builder.field(stringify!($field), $crate::types::FieldType::from($ty));
+            $($($rest)*)?
+        )
+    };
+    ($name:ident; $builder:expr; $field:ident : $kind:ident ($($args:tt)*) $(, $($rest:tt)*)?) => {
+        $crate::__struct_type_fields!(
+            $name;
+            $builder.field(
+                stringify!($field),
+                $crate::__struct_type_field!($name; $kind($($args)*)),
+            );
+            $($($rest)*)?
+        )
+    };
+    ($name:ident; $builder:expr; $field:ident : $kind:ident $(, $($rest:tt)*)?) => {
+        $crate::__struct_type_fields!(
+            $name;
+            $builder.field(stringify!($field), $crate::__struct_type_field!($name; $kind));
+            $($($rest)*)?
+        )
+    };
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __struct_type_field {
+    ($name:ident; Self) => {
+        compile_error!("a struct cannot contain itself; use `ptr(Self)`")
+    };
+    ($name:ident; ptr(Self)) => {
+        $crate::types::FieldType::Pointer(Box::new($crate::types::FieldType::forward(
+            stringify!($name),
+        )))
+    };
+    ($name:ident; ptr({ $ty:expr })) => {
+        $crate::types::FieldType::Pointer(Box::new($crate::types::FieldType::from($ty)))
+    };
+    ($name:ident; ptr($kind:ident $(($($args:tt)*))?)) => {
+        $crate::types::FieldType::Pointer(Box::new($crate::__struct_type_field!(
+            $name;
+            $kind $(($($args)*))?
+        )))
+    };
+    ($name:ident; array({ $ty:expr }, $len:expr)) => {
+        $crate::types::FieldType::Array(Box::new($crate::types::FieldType::from($ty)), $len)
+    };
+    ($name:ident; array($kind:ident $(($($args:tt)*))?, $len:expr)) => {
+        $crate::types::FieldType::Array(
+            Box::new($crate::__struct_type_field!($name; $kind $(($($args)*))?)),
+            $len,
+        )
+    };
+    ($name:ident; $prim:ident) => {
+        $crate::types::FieldType::from($crate::types::builders::$prim())
+    };
+}
+
+/// Start building an enum from a near-C member list
+///
+/// Expands to an `EnumBuilder` of the given width in bytes, with `member`
+/// calls for members with a value and `auto_member` calls for the rest,
+/// which follow the previous member:
+///
+/// ```ignore
+/// let color = enum_type!(Color: 1 { RED = 0, GREEN, BLUE }).build()?;
+/// let access = enum_type!(bitmask Access: 4 { READ = 1, WRITE = 2, EXEC = 4 }).build()?;
+/// ```
+///
+/// A leading `bitmask` keyword marks the enum as a set of OR-able flags, whose
+/// members without a value take the next bit after the previous member, so
+/// `enum_type!(bitmask F: 4 { A, B, C })` gives 1, 2 and 4.
+#[macro_export]
+macro_rules! enum_type {
+    (bitmask $name:ident : $width:literal { $($body:tt)* }) => {
+        $crate::__enum_type_members!(
+            $crate::types::builders::enum_type(stringify!($name), $width).bitmask(true);
+            $($body)*
+        )
+    };
+    ($name:ident : $width:literal { $($body:tt)* }) => {
+        $crate::__enum_type_members!(
+            $crate::types::builders::enum_type(stringify!($name), $width);
+            $($body)*
+        )
+    };
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __enum_type_members {
+    ($builder:expr;) => {
+        $builder
+    };
+    ($builder:expr; $member:ident = $value:expr $(, $($rest:tt)*)?) => {
+        $crate::__enum_type_members!(
+            $builder.member(stringify!($member), $value);
+            $($($rest)*)?
+        )
+    };
+    ($builder:expr; $member:ident $(, $($rest:tt)*)?) => {
+        $crate::__enum_type_members!(
+            $builder.auto_member(stringify!($member));
+            $($($rest)*)?
+        )
+    };
+}
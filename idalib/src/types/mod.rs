@@ -5,9 +5,20 @@ pub use types_core::*;
 // Export the builder module
 pub mod builder;
 
+// Declarative type definition macros
+mod macros;
+
+// JSON type documents
+#[cfg(feature = "serde")]
+pub mod schema;
+
 // Re-export commonly used builder items at the module level
 pub use builder::{
-    builders, FieldType, PrimitiveType, StructBuilder, TypeBuilder,
+    builders, FieldType, PrimitiveType, StructBuilder, TypeBuilder, TypeValidator,
     EnumBuilder, ArrayBuilder, PointerBuilder,
     FunctionBuilder, FunctionPointerBuilder, CallingConvention,
-};
\ No newline at end of file
+    TypedefBuilder, TypeRegistry, EnumRepr,
+};
+
+#[cfg(feature = "serde")]
+pub use schema::TypeDefinition;
\ No newline at end of file
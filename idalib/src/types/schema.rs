@@ -0,0 +1,258 @@
+//! JSON documents describing sets of types, for keeping type definitions
+//! under version control and moving them between databases
+
+use std::collections::HashSet;
+
+use serde::{Deserialize, Serialize};
+
+use super::Type;
+use super::builder::{
+    EnumBuilder, FieldType, StructBuilder, TypeRegistry, TypeStep, TypeValidator, TypedefBuilder,
+};
+use crate::IDAError;
+use crate::ffi::types::{
+    describe_typedef_target, describe_udt_member_type, get_enum_member_count, get_enum_member_name,
+    get_enum_member_value, get_type_name, get_type_size, get_udt_member_bitfield_storage,
+    get_udt_member_bitfields, get_udt_member_count, get_udt_member_names, get_udt_member_offsets,
+    get_udt_member_sizes, idalib_get_type_ordinal_limit, idalib_is_valid_type_ordinal,
+    is_bitmask_enum, is_library_type, is_union_type,
+};
+
+/// One entry of a JSON type document
+///
+/// Entries refer to each other by name through `FieldType::ForwardRef`, and
+/// to types already in the database through `FieldType::Existing`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum TypeDefinition {
+    /// A struct or union
+    Struct(StructBuilder),
+    Enum(EnumBuilder),
+    Typedef(TypedefBuilder),
+}
+
+impl TypeDefinition {
+    /// Get the name of the defined type
+    pub fn name(&self) -> &str {
+        match self {
+            TypeDefinition::Struct(builder) => builder.name(),
+            TypeDefinition::Enum(builder) => builder.name(),
+            TypeDefinition::Typedef(builder) => builder.name(),
+        }
+    }
+}
+
+/// Build the types described by a JSON array of `TypeDefinition`s
+///
+/// Enums are built first, then typedefs, then structs and unions, so structs
+/// may embed anything else in the document. Types are returned in document
+/// order. If any entry fails, the types built for the document are deleted
+/// again.
+pub(crate) fn import_json(json: &str) -> Result<Vec<Type>, IDAError> {
+    let entries: Vec<serde_json::Value> = serde_json::from_str(json)
+        .map_err(|e| IDAError::validation(format!("Invalid type document: {e}")))?;
+    let definitions = entries
+        .into_iter()
+        .enumerate()
+        .map(|(index, entry)| {
+            TypeDefinition::deserialize(entry)
+                .map_err(|e| IDAError::validation(format!("Invalid entry {index}: {e}")))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    // Don't leave part of the document behind
+    let mut registry = TypeRegistry::new();
+    let snapshot = registry.snapshot();
+    build_definitions(definitions, &mut registry).inspect_err(|_| registry.rollback(snapshot))
+}
+
+/// Build the entries of a type document into `registry`, in the order
+/// described for `import_json`
+fn build_definitions(
+    definitions: Vec<TypeDefinition>,
+    registry: &mut TypeRegistry,
+) -> Result<Vec<Type>, IDAError> {
+    let failed = |index: usize, name: &str, e: IDAError| {
+        IDAError::with_kind(
+            e.kind(),
+            format!("Failed to build entry {index} ('{name}'): {}", e.message()),
+        )
+    };
+
+    let mut types: Vec<Option<Type>> = vec![None; definitions.len()];
+    let mut structs = Vec::new();
+    let mut typedefs = Vec::new();
+    for (index, definition) in definitions.into_iter().enumerate() {
+        match definition {
+            TypeDefinition::Enum(builder) => {
+                let name = builder.name().to_owned();
+                let typ = builder
+                    .build_into(registry)
+                    .map_err(|e| failed(index, &name, e))?;
+                types[index] = Some(typ);
+            }
+            TypeDefinition::Struct(builder) => {
+                TypeValidator::validate(&builder).map_err(|e| failed(index, builder.name(), e))?;
+                structs.push((index, builder));
+            }
+            TypeDefinition::Typedef(builder) => typedefs.push((index, builder)),
+        }
+    }
+
+    for (index, builder) in typedefs {
+        let name = builder.name().to_owned();
+        let typ = builder
+            .build_into(registry)
+            .map_err(|e| failed(index, &name, e))?;
+        types[index] = Some(typ);
+    }
+
+    // Structs are built together so they can refer to each other
+    let (indices, builders): (Vec<_>, Vec<_>) = structs.into_iter().unzip();
+    let built = registry.build_all(builders).map_err(|e| {
+        IDAError::with_kind(
+            e.kind(),
+            format!("Failed to build structs and unions: {}", e.message()),
+        )
+    })?;
+    for (index, typ) in indices.into_iter().zip(built) {
+        types[index] = Some(typ);
+    }
+
+    Ok(types.into_iter().flatten().collect())
+}
+
+/// Describe the named structs, unions, enums and typedefs of the database as
+/// a JSON array of `TypeDefinition`s, in ordinal order
+///
+/// Types imported from the base type libraries are skipped unless
+/// `include_library_types` is set; references to them are kept by name.
+pub(crate) fn export_json(include_library_types: bool) -> Result<String, IDAError> {
+    let limit = match unsafe { idalib_get_type_ordinal_limit() } {
+        u32::MAX => 0,
+        limit => limit,
+    };
+
+    let mut entries = Vec::new();
+    for ordinal in 1..limit {
+        if !unsafe { idalib_is_valid_type_ordinal(ordinal) } {
+            continue;
+        }
+        let name = get_type_name(ordinal);
+        if name.is_empty() || (!include_library_types && is_library_type(ordinal)) {
+            continue;
+        }
+        let kind = if get_udt_member_count(ordinal) >= 0 {
+            ExportKind::Struct
+        } else if get_enum_member_count(ordinal) >= 0 {
+            ExportKind::Enum
+        } else {
+            match TypeStep::parse_all(describe_typedef_target(ordinal)) {
+                // Function prototypes have no entry kind
+                steps if steps.is_empty() => continue,
+                steps => ExportKind::Typedef(steps),
+            }
+        };
+        entries.push((ordinal, name, kind));
+    }
+
+    let names: HashSet<String> = entries.iter().map(|(_, name, _)| name.clone()).collect();
+    let definitions: Vec<TypeDefinition> = entries
+        .into_iter()
+        .map(|(ordinal, name, kind)| match kind {
+            ExportKind::Struct => Ok(TypeDefinition::Struct(export_struct(
+                ordinal, name, &names,
+            )?)),
+            ExportKind::Enum => Ok(TypeDefinition::Enum(export_enum(ordinal, name))),
+            ExportKind::Typedef(steps) => {
+                let target = export_field_type(&steps, &names)
+                    .ok_or_else(|| IDAError::ffi_with(format!("Failed to describe '{name}'")))?;
+                Ok(TypeDefinition::Typedef(TypedefBuilder::new(name, target)))
+            }
+        })
+        .collect::<Result<_, IDAError>>()?;
+
+    serde_json::to_string_pretty(&definitions)
+        .map_err(|e| IDAError::ffi_with(format!("Failed to serialize types: {e}")))
+}
+
+enum ExportKind {
+    Struct,
+    Enum,
+    /// Typedef of the type with this description
+    Typedef(Vec<TypeStep>),
+}
+
+/// Describe a struct/union with explicit member offsets, so the layout is
+/// reproduced regardless of packing
+fn export_struct(
+    ordinal: u32,
+    name: String,
+    names: &HashSet<String>,
+) -> Result<StructBuilder, IDAError> {
+    let is_union = is_union_type(ordinal);
+    let mut builder = StructBuilder::new(name).is_union(is_union);
+    let members = get_udt_member_names(ordinal)
+        .into_iter()
+        .zip(get_udt_member_offsets(ordinal))
+        .zip(get_udt_member_sizes(ordinal))
+        .zip(get_udt_member_bitfields(ordinal));
+    for (index, (((member_name, offset_bits), size_bits), bitfield)) in (0..).zip(members) {
+        builder = match bitfield {
+            -1 => {
+                let steps = TypeStep::parse_all(describe_udt_member_type(ordinal, index));
+                let field_type = export_field_type(&steps, names).ok_or_else(|| {
+                    IDAError::ffi_with(format!(
+                        "Failed to describe member '{member_name}' of '{}'",
+                        builder.name()
+                    ))
+                })?;
+                builder.field_at(member_name, field_type, offset_bits / 8)
+            }
+            is_unsigned => builder.bitfield_with_storage(
+                member_name,
+                offset_bits as u32,
+                size_bits as u32,
+                is_unsigned == 1,
+                get_udt_member_bitfield_storage(ordinal, index),
+            ),
+        };
+    }
+    Ok(match get_type_size(ordinal) {
+        size if size > 0 && !is_union => builder.size(size),
+        _ => builder,
+    })
+}
+
+fn export_enum(ordinal: u32, name: String) -> EnumBuilder {
+    let mut builder =
+        EnumBuilder::new(name, get_type_size(ordinal) as u32).bitmask(is_bitmask_enum(ordinal));
+    for index in 0..get_enum_member_count(ordinal).max(0) as u32 {
+        builder = builder.member(
+            get_enum_member_name(ordinal, index),
+            get_enum_member_value(ordinal, index),
+        );
+    }
+    builder
+}
+
+/// Describe a type from its steps, referring to exported types by name and
+/// declaring anything without an ordinal in place
+fn export_field_type(steps: &[TypeStep], names: &HashSet<String>) -> Option<FieldType> {
+    FieldType::from_steps(steps, &mut |ordinal| {
+        let name = get_type_name(ordinal);
+        if !name.is_empty() {
+            return Some(if names.contains(&name) {
+                FieldType::ForwardRef(name)
+            } else {
+                FieldType::Existing(Type::from_ordinal(ordinal))
+            });
+        }
+        if get_udt_member_count(ordinal) >= 0 {
+            let inner = export_struct(ordinal, String::new(), names).ok()?;
+            return Some(FieldType::Inline(Box::new(inner)));
+        }
+        // Anything else is kept as a C type expression
+        Some(FieldType::Existing(Type::from_ordinal(ordinal)))
+    })
+}
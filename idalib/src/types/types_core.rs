@@ -1,10 +1,17 @@
+use std::fmt;
 use std::marker::PhantomData;
 
+use super::builder::ffi_error;
 use crate::ffi::types::{
-    idalib_apply_type_by_ordinal, idalib_get_type_ordinal_limit, idalib_is_valid_type_ordinal,
-    idalib_tinfo_get_name_by_ordinal,
+    add_field_to_type, compare_types, finalize_type, get_array_element_ordinal, get_array_length,
+    get_enum_member_by_value, get_enum_member_count, get_enum_member_name, get_enum_member_value,
+    get_pointer_target, get_type_alignment, get_type_kind, get_type_size, get_udt_member_bitfields,
+    get_udt_member_count, get_udt_member_names, get_udt_member_offsets, get_udt_member_sizes,
+    get_udt_member_types, idalib_apply_type_by_ordinal, idalib_get_type_ordinal_limit,
+    idalib_is_valid_type_ordinal, idalib_tinfo_get_name_by_ordinal, is_pointer_type,
+    print_type_decl, rename_udt_member, set_udt_member_type,
 };
-use crate::idb::IDB;
+use crate::idb::{IDB, ensure_writable};
 use crate::{Address, IDAError};
 
 pub type TypeIndex = u32;
@@ -22,7 +29,58 @@ pub enum TypeFlags {
     STRICT = 0x0004,
 }
 
-#[derive(Debug)]
+/// Kind of a type, as returned by `Type::kind`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TypeKind {
+    /// A basic type like `int` or `void`
+    Primitive,
+    Struct,
+    Union,
+    Enum,
+    Pointer,
+    Array,
+    /// A function prototype
+    Function,
+    /// A named alias of another type
+    Typedef,
+    /// The type does not exist
+    Unknown,
+}
+
+/// A member of a struct or union type
+#[derive(Debug, Clone)]
+pub struct FieldInfo {
+    /// Member name; empty for anonymous members
+    pub name: String,
+    /// Type of the member, or `None` for bitfields and member types without an
+    /// ordinal of their own, like pointers declared in place; reading the
+    /// members never adds types to the type library
+    pub ty: Option<Type>,
+    /// Offset of the member in bytes; for bitfields, of the byte holding the
+    /// first bit
+    pub offset: u64,
+    /// Size of the member in bytes; for bitfields, of the whole bytes holding
+    /// it
+    pub size: u64,
+    /// Offset of a bitfield in bits from the start of the type
+    pub bit_offset: Option<u32>,
+    /// Width of a bitfield in bits
+    pub bit_width: Option<u32>,
+}
+
+impl FieldInfo {
+    /// Byte range occupied by the member, including the whole bytes holding a
+    /// bitfield
+    fn extent(&self) -> (u64, u64) {
+        (self.offset, self.offset + self.size)
+    }
+}
+
+/// A type in the local type library of the database
+///
+/// Equality and hashing go by ordinal, so a `Type` can key maps and sets;
+/// use `structurally_equals` to compare the definitions of two types.
+#[derive(Debug, PartialEq, Eq, Hash)]
 pub struct Type {
     // We'll store the type ordinal instead of the tinfo_t directly
     ordinal: TypeIndex,
@@ -33,6 +91,11 @@ impl Type {
         Self { ordinal }
     }
 
+    /// Get the name of this type, or `None` if it is anonymous
+    ///
+    /// The name is not cached, since the type may be renamed; each call looks
+    /// it up in the type library, so keep the result when displaying many
+    /// types.
     pub fn name(&self) -> Option<String> {
         let name = unsafe { idalib_tinfo_get_name_by_ordinal(self.ordinal) }.ok()?;
         if name.is_empty() {
@@ -42,6 +105,12 @@ impl Type {
         }
     }
 
+    /// Get the name of this type, or `type_<ordinal>` if it is anonymous
+    pub fn name_or_default(&self) -> String {
+        self.name()
+            .unwrap_or_else(|| format!("type_{}", self.ordinal))
+    }
+
     /// Apply this type to an address with default flags (TINFO_DEFINITE)
     pub fn apply_to_address(&self, address: Address) -> Result<(), IDAError> {
         self.apply_to_address_with_flags(address, TypeFlags::DEFINITE)
@@ -53,6 +122,7 @@ impl Type {
         address: Address,
         flags: TypeFlags,
     ) -> Result<(), IDAError> {
+        ensure_writable()?;
         let success =
             unsafe { idalib_apply_type_by_ordinal(address.into(), self.ordinal, flags as u32) };
         if success {
@@ -62,12 +132,357 @@ impl Type {
         }
     }
 
+    /// Get the name of the first member of this enum with the given value
+    ///
+    /// Returns `None` if this is not an enum or no member has the value.
+    pub fn enum_member_by_value(&self, value: i64) -> Option<String> {
+        let name = get_enum_member_by_value(self.ordinal, value);
+        if name.is_empty() {
+            None
+        } else {
+            Some(name)
+        }
+    }
+
+    /// Get the element type of this array
+    ///
+    /// Returns `None` if this is not an array, or if the element type has no
+    /// ordinal of its own; this never adds types to the type library.
+    pub fn array_element(&self) -> Option<Type> {
+        match get_array_element_ordinal(self.ordinal) {
+            0 => None,
+            ordinal => Some(Type::from_ordinal(ordinal)),
+        }
+    }
+
+    /// Get the number of elements of this array
+    ///
+    /// Returns `None` if this is not an array.
+    pub fn array_length(&self) -> Option<u64> {
+        u64::try_from(get_array_length(self.ordinal)).ok()
+    }
+
+    /// Check whether this is a pointer type
+    pub fn is_pointer(&self) -> bool {
+        is_pointer_type(self.ordinal)
+    }
+
+    /// Get the type this pointer points to
+    ///
+    /// Returns `None` if this is not a pointer, or if the target type has no
+    /// ordinal of its own; this never adds types to the type library.
+    pub fn pointer_target(&self) -> Option<Type> {
+        match get_pointer_target(self.ordinal) {
+            0 => None,
+            ordinal => Some(Type::from_ordinal(ordinal)),
+        }
+    }
+
+    /// Render this type as a C declaration
+    ///
+    /// Structs, unions and enums are printed with their full body, e.g.
+    /// `struct Name { ... };`, including anonymous members and bitfields.
+    pub fn to_c_decl(&self) -> Result<String, IDAError> {
+        let decl = print_type_decl(self.ordinal);
+        if decl.is_empty() {
+            Err(IDAError::ffi_with(format!(
+                "Failed to print type with ordinal {}",
+                self.ordinal
+            )))
+        } else {
+            Ok(decl)
+        }
+    }
+
+    /// Get the size of this type in bytes
+    ///
+    /// Returns `None` for incomplete and opaque types, like forward declared
+    /// structs and `void`.
+    pub fn size(&self) -> Option<u64> {
+        match get_type_size(self.ordinal) {
+            0 => None,
+            size => Some(size),
+        }
+    }
+
+    /// Get the natural alignment of this type in bytes
+    ///
+    /// Returns `None` for incomplete and opaque types.
+    pub fn alignment(&self) -> Option<u64> {
+        self.size()?;
+        match get_type_alignment(self.ordinal) {
+            0 => None,
+            align => Some(align),
+        }
+    }
+
+    /// Get the kind of this type
+    ///
+    /// Typedefs are reported as `TypeKind::Typedef` rather than the kind of
+    /// the type they alias.
+    pub fn kind(&self) -> TypeKind {
+        match get_type_kind(self.ordinal) {
+            1 => TypeKind::Primitive,
+            2 => TypeKind::Struct,
+            3 => TypeKind::Union,
+            4 => TypeKind::Enum,
+            5 => TypeKind::Pointer,
+            6 => TypeKind::Array,
+            7 => TypeKind::Function,
+            8 => TypeKind::Typedef,
+            _ => TypeKind::Unknown,
+        }
+    }
+
+    /// Get the members of this struct or union, in declaration order
+    pub fn fields(&self) -> Result<Vec<FieldInfo>, IDAError> {
+        if get_udt_member_count(self.ordinal) < 0 {
+            return Err(IDAError::ffi_with(format!(
+                "Type #{} is not a struct or union",
+                self.ordinal
+            )));
+        }
+
+        // The members are fetched once per attribute rather than per member
+        let members = get_udt_member_names(self.ordinal)
+            .into_iter()
+            .zip(get_udt_member_types(self.ordinal))
+            .zip(get_udt_member_offsets(self.ordinal))
+            .zip(get_udt_member_sizes(self.ordinal))
+            .zip(get_udt_member_bitfields(self.ordinal));
+        let fields = members
+            .map(
+                |((((name, member_type), offset_bits), size_bits), bitfield)| {
+                    let is_bitfield = bitfield >= 0;
+                    FieldInfo {
+                        name,
+                        ty: (member_type != 0 && !is_bitfield)
+                            .then(|| Type::from_ordinal(member_type)),
+                        offset: offset_bits / 8,
+                        size: (offset_bits + size_bits).div_ceil(8) - offset_bits / 8,
+                        bit_offset: is_bitfield.then_some(offset_bits as u32),
+                        bit_width: is_bitfield.then_some(size_bits as u32),
+                    }
+                },
+            )
+            .collect();
+        Ok(fields)
+    }
+
+    /// Change the type of the member called `field_name`
+    ///
+    /// The new type must fit in the member's slot, the bytes up to the next
+    /// member or the end of the type; use `set_field_type_with` to let it
+    /// grow over the members that follow.
+    pub fn set_field_type(&self, field_name: &str, new_ty: &Type) -> Result<(), IDAError> {
+        self.set_field_type_with(field_name, new_ty, false)
+    }
+
+    /// Change the type of the member called `field_name`, letting it grow
+    /// over the members that follow if `allow_growth` is set
+    ///
+    /// Members overwritten by the grown member are removed.
+    pub fn set_field_type_with(
+        &self,
+        field_name: &str,
+        new_ty: &Type,
+        allow_growth: bool,
+    ) -> Result<(), IDAError> {
+        ensure_writable()?;
+        let fields = self.fields()?;
+        let index = self.field_index(&fields, field_name)?;
+        if fields[index].bit_width.is_some() {
+            return Err(IDAError::unsupported(format!(
+                "Cannot change the type of bitfield '{field_name}'"
+            )));
+        }
+
+        let new_size = new_ty.size().ok_or_else(|| {
+            IDAError::validation(format!("Type #{} has no known size", new_ty.ordinal))
+        })?;
+        if !allow_growth {
+            let slot = self.field_slot(&fields, index);
+            if new_size > slot {
+                return Err(IDAError::validation(format!(
+                    "Type #{} ({new_size} bytes) does not fit in the {slot} bytes of member '{field_name}'",
+                    new_ty.ordinal
+                )));
+            }
+        }
+
+        if !set_udt_member_type(self.ordinal, field_name, new_ty.ordinal, allow_growth) {
+            return Err(ffi_error(format!(
+                "Failed to change the type of member '{field_name}'"
+            )));
+        }
+        Ok(())
+    }
+
+    /// Rename the member called `old` to `new`
+    ///
+    /// Fails if the type already has a member called `new`.
+    pub fn rename_field(&self, old: &str, new: &str) -> Result<(), IDAError> {
+        ensure_writable()?;
+        let fields = self.fields()?;
+        self.field_index(&fields, old)?;
+        if new.is_empty() {
+            return Err(IDAError::validation("Member name cannot be empty"));
+        }
+        if old == new {
+            return Ok(());
+        }
+        if fields.iter().any(|field| field.name == new) {
+            return Err(IDAError::already_exists(format!(
+                "Type #{} already has a member '{new}'",
+                self.ordinal
+            )));
+        }
+
+        if !rename_udt_member(self.ordinal, old, new) {
+            return Err(ffi_error(format!(
+                "Failed to rename member '{old}' to '{new}'"
+            )));
+        }
+        Ok(())
+    }
+
+    /// Add a member after the last member of this struct, at the next offset
+    /// suitably aligned for `ty`, or as another member of this union
+    pub fn append_field(&self, name: &str, ty: &Type) -> Result<(), IDAError> {
+        let offset = if self.kind() == TypeKind::Union {
+            0
+        } else {
+            let end = self.size().unwrap_or(0);
+            end.next_multiple_of(ty.alignment().unwrap_or(1))
+        };
+        self.insert_field_at(name, ty, offset)
+    }
+
+    /// Add a member at `offset` bytes into this struct
+    ///
+    /// The new member must not overlap an existing one; union members are
+    /// always at offset 0.
+    pub fn insert_field_at(&self, name: &str, ty: &Type, offset: u64) -> Result<(), IDAError> {
+        ensure_writable()?;
+        let fields = self.fields()?;
+        if name.is_empty() {
+            return Err(IDAError::validation("Member name cannot be empty"));
+        }
+        if fields.iter().any(|field| field.name == name) {
+            return Err(IDAError::already_exists(format!(
+                "Type #{} already has a member '{name}'",
+                self.ordinal
+            )));
+        }
+
+        let size = ty.size().ok_or_else(|| {
+            IDAError::validation(format!("Type #{} has no known size", ty.ordinal))
+        })?;
+        if self.kind() == TypeKind::Union {
+            if offset != 0 {
+                return Err(IDAError::validation(format!(
+                    "Union member '{name}' must be at offset 0, not {offset}"
+                )));
+            }
+        } else if let Some(field) = fields.iter().find(|field| {
+            let (start, end) = field.extent();
+            start < offset + size && offset < end
+        }) {
+            return Err(IDAError::validation(format!(
+                "Member '{name}' at offset {offset} overlaps member '{}'",
+                field.name
+            )));
+        }
+
+        if !add_field_to_type(self.ordinal, name, ty.ordinal, offset) {
+            return Err(ffi_error(format!("Failed to add member '{name}'")));
+        }
+        if !finalize_type(self.ordinal) {
+            return Err(ffi_error(format!(
+                "Failed to finalize type #{} after adding member '{name}'",
+                self.ordinal
+            )));
+        }
+        Ok(())
+    }
+
+    /// Index of the named member in `fields`
+    fn field_index(&self, fields: &[FieldInfo], field_name: &str) -> Result<usize, IDAError> {
+        if field_name.is_empty() {
+            return Err(IDAError::validation("Member name cannot be empty"));
+        }
+        fields
+            .iter()
+            .position(|field| field.name == field_name)
+            .ok_or_else(|| {
+                IDAError::not_found_with(format!(
+                    "Type #{} has no member '{field_name}'",
+                    self.ordinal
+                ))
+            })
+    }
+
+    /// Bytes available to the member at `index`: up to the next member, or
+    /// to the end of the type for the last member and union members
+    fn field_slot(&self, fields: &[FieldInfo], index: usize) -> u64 {
+        let offset = fields[index].offset;
+        let size = self.size().unwrap_or(0);
+        if self.kind() == TypeKind::Union {
+            return size;
+        }
+        fields
+            .iter()
+            .map(|field| field.offset)
+            .filter(|&next| next > offset)
+            .min()
+            .unwrap_or(size.max(offset))
+            - offset
+    }
+
+    /// Get the `(name, value)` pairs of this enum, in declaration order
+    pub fn enum_members(&self) -> Result<Vec<(String, i64)>, IDAError> {
+        let count = u32::try_from(get_enum_member_count(self.ordinal)).map_err(|_| {
+            IDAError::unsupported(format!("Type #{} is not an enum", self.ordinal))
+        })?;
+
+        Ok((0..count)
+            .map(|index| {
+                (
+                    get_enum_member_name(self.ordinal, index),
+                    get_enum_member_value(self.ordinal, index),
+                )
+            })
+            .collect())
+    }
+
+    /// Check whether this type has the same definition as `other`
+    ///
+    /// Names are not compared, so two separately built structs with the same
+    /// members are structurally equal even though their ordinals differ.
+    pub fn structurally_equals(&self, other: &Type) -> bool {
+        self.same_ordinal(other) || compare_types(self.ordinal, other.ordinal)
+    }
+
+    /// Check whether this and `other` refer to the same type library entry
+    pub fn same_ordinal(&self, other: &Type) -> bool {
+        self.ordinal == other.ordinal
+    }
+
     /// Get the ordinal (index) of this type
     pub fn ordinal(&self) -> TypeIndex {
         self.ordinal
     }
 }
 
+impl fmt::Display for Type {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.to_c_decl() {
+            Ok(decl) => f.write_str(&decl),
+            Err(_) => write!(f, "<type #{}>", self.ordinal),
+        }
+    }
+}
+
 pub struct TypeList<'a> {
     _marker: PhantomData<&'a IDB>,
 }
@@ -136,3 +551,19 @@ impl<'s, 'a> Iterator for TypeListIter<'s, 'a> {
         None
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn types_compare_by_ordinal() {
+        let mut seen = HashSet::new();
+        assert!(seen.insert(Type::from_ordinal(5)));
+        assert!(!seen.insert(Type::from_ordinal(5)));
+        assert!(seen.insert(Type::from_ordinal(6)));
+        assert_eq!(seen.len(), 2);
+        assert_eq!(Type::from_ordinal(7), Type::from_ordinal(7).clone());
+    }
+}